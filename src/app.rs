@@ -1,12 +1,25 @@
 use bevy::app::AppExit;
 use bevy::prelude::*;
 use bevy_egui::egui;
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, mpsc};
+use std::time::Duration;
 
-use crate::protocol;
-use crate::telemetry::{DataBuffer, PidAxis};
-use crate::uart::{self, UartCommand};
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+
+use crate::config::BAUD_RATE;
+use crate::export;
+use crate::log_buffer::LogBuffer;
+use crate::persistence::{ConnectionProfile, PersistentSettings, SettingsReset};
+use crate::protocol::{self, QueuedCommand};
+use crate::telemetry::{AngleUnit, DataBuffer, LogLevel, PidAxis, PidTerm, XAxisMode, YawDisplay};
+use crate::status::StatusChannel;
+use crate::transport::{Endpoint, TransportKind};
+use crate::uart::{self, ConnectionState, UartCommand};
+
+/// Queued commands are sent one per this interval
+pub const COMMAND_INTERVAL: Duration = Duration::from_millis(200);
 
 #[derive(Resource)]
 pub struct CommandTimer {
@@ -16,31 +29,75 @@ pub struct CommandTimer {
 impl Default for CommandTimer {
     fn default() -> Self {
         Self {
-            timer: Timer::from_seconds(0.2, TimerMode::Repeating),
+            timer: Timer::new(COMMAND_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Interval timer for the periodic ConfigPacket resend
+#[derive(Resource)]
+pub struct ConfigRefreshTimer {
+    pub timer: Timer,
+}
+
+impl Default for ConfigRefreshTimer {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(5.0, TimerMode::Repeating),
         }
     }
 }
 
+/// Pending "Export Plot" request. The plot fills in where it was drawn, in physical pixels.
+#[derive(Clone, Copy, Debug)]
+pub struct PlotCapture {
+    pub plot_id: &'static str,
+    pub rect: Option<egui::Rect>,
+}
+
+/// Top visible log line, so the view stays put while lines are added and dropped
+#[derive(Clone, Copy, Debug)]
+pub struct LogScrollAnchor {
+    pub seq: u64,
+    /// Pixels scrolled past the top of that line
+    pub row_offset: f32,
+    pub at_bottom: bool,
+}
+
 #[derive(Resource, Default)]
 pub struct CommandQueue {
-    pub queue: Arc<Mutex<VecDeque<protocol::CommandType>>>,
+    pub queue: Arc<Mutex<VecDeque<QueuedCommand>>>,
 }
 
 impl CommandQueue {
     pub fn enqueue(&self, command: protocol::CommandType) {
         if let Ok(mut queue) = self.queue.lock() {
-            // Remove any existing command of the same type
+            // Remove any existing command of the same type, unless a batch is waiting on it
             let cmd_discriminant = std::mem::discriminant(&command);
-            queue.retain(|existing_cmd| {
-                std::mem::discriminant(existing_cmd) != cmd_discriminant
+            queue.retain(|existing| {
+                existing.batch.is_some() || std::mem::discriminant(&existing.command) != cmd_discriminant
             });
-            queue.push_back(command);
+            queue.push_back(QueuedCommand { command, batch: None });
         }
     }
 
-    pub fn dequeue(&self) -> Option<Vec<u8>> {
+    /// Appends a command without replacing pending commands of the same type
+    pub fn enqueue_uncoalesced(&self, command: protocol::CommandType) {
         if let Ok(mut queue) = self.queue.lock() {
-            queue.pop_front().map(|cmd| cmd.to_binary_frame())
+            queue.push_back(QueuedCommand { command, batch: None });
+        }
+    }
+
+    /// Appends a member of a "Send All" batch; later coalescing enqueues leave it in place
+    pub fn enqueue_batch(&self, command: protocol::CommandType, batch: u32) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.push_back(QueuedCommand { command, batch: Some(batch) });
+        }
+    }
+
+    pub fn dequeue(&self) -> Option<QueuedCommand> {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.pop_front()
         } else {
             None
         }
@@ -50,14 +107,79 @@ impl CommandQueue {
 #[derive(Resource, Clone)]
 pub struct AppState {
     pub data_buffer: Arc<Mutex<DataBuffer>>,
+    /// Log panel contents; `data_buffer` queues its messages here, applied each frame
+    pub log_buffer: Arc<Mutex<LogBuffer>>,
     pub serial_connected: bool,
+    pub connection_state: Arc<Mutex<ConnectionState>>,
+    /// Reports from background threads, drained into the log each frame
+    pub status: Arc<StatusChannel>,
     pub port_path: String,
+    pub baud_rate: u32,
+    /// Serial port or TCP bridge
+    pub transport: TransportKind,
+    /// host:port of the TCP bridge
+    pub tcp_address: String,
+    /// Hex-dump received bytes to a file in `raw_capture_dir()`
+    pub raw_capture: bool,
+    /// Link the active UART thread was opened with
+    pub connected_endpoint: Option<Endpoint>,
+    /// Name typed into the "save profile" field
+    pub new_profile_name: String,
     pub selected_pid_axis: PidAxis,
+    /// PID plot shows `selected_pid_term` for all axes instead of P/I/D of one axis
+    pub pid_plot_combined: bool,
+    pub selected_pid_term: PidTerm,
     pub auto_scroll_logs: bool,
+    /// Manual raw send field in the commands panel
+    pub manual_send_text: String,
+    /// Interpret `manual_send_text` as hex bytes instead of ASCII
+    pub manual_send_hex: bool,
+    /// Set while the log view is scrolled away from the newest messages
+    pub log_scroll_anchor: Option<LogScrollAnchor>,
     pub uart_sender: Option<mpsc::Sender<UartCommand>>,
+    /// Cleared by the UART thread when it exits
+    pub uart_alive: Option<Arc<AtomicBool>>,
     pub viewport_texture_id: Option<egui::TextureId>,
     pub available_ports: Vec<String>,
     pub show_pid_tuning: bool,
+    /// Axes the PID window's "Send Selected" sends
+    pub pid_send_axes: Vec<protocol::SelectPID>,
+    /// Label typed for the next timeline marker
+    pub marker_text: String,
+    /// Reset waiting for the user to confirm it
+    pub pending_settings_reset: Option<SettingsReset>,
+    /// Settings as they were before the last reset, for a one-level undo
+    pub settings_undo: Option<(SettingsReset, PersistentSettings)>,
+    pub show_config_summary: bool,
+    pub show_setpoints: bool,
+    pub show_rate_plot: bool,
+    pub show_fft_plot: bool,
+    pub show_artificial_horizon: bool,
+    /// Plots show only the last N seconds; None shows the whole buffer
+    pub plot_window_secs: Option<f32>,
+    pub plot_x_mode: XAxisMode,
+    /// Draw every sample instead of a min/max envelope per pixel column
+    pub plot_full_resolution: bool,
+    /// ESC plot shows currents rather than temperatures
+    pub esc_plot_currents: bool,
+    /// Display unit for attitude and angular rates everywhere in the UI
+    pub angle_unit: AngleUnit,
+    pub yaw_display: YawDisplay,
+    /// X of the synchronized cursor drawn on every plot
+    pub plot_cursor_x: Option<f64>,
+    /// X under the pointer in the plot hovered during the current frame
+    pub plot_hover_x: Option<f64>,
+    /// Plot waiting to be saved as PNG by `plot_export_system`
+    pub plot_capture: Option<PlotCapture>,
+    /// Telemetry field names shown in the custom plot
+    pub custom_plot_fields: Vec<String>,
+    /// Plots drawn as stairs rather than interpolated lines, by plot id
+    pub stepped_plots: HashSet<&'static str>,
+    pub new_mute_pattern: String,
+    pub show_log_info: bool,
+    pub show_log_warn: bool,
+    pub show_log_error: bool,
+    pub show_log_sent: bool,
 }
 
 impl Default for AppState {
@@ -74,40 +196,184 @@ impl Default for AppState {
             }
         });
 
+        let log_buffer = LogBuffer::new();
         Self {
-            data_buffer: Arc::new(Mutex::new(DataBuffer::new())),
+            data_buffer: Arc::new(Mutex::new(DataBuffer::new(log_buffer.sender()))),
+            log_buffer: Arc::new(Mutex::new(log_buffer)),
             serial_connected: false,
+            connection_state: Arc::new(Mutex::new(ConnectionState::default())),
+            status: Arc::new(StatusChannel::default()),
             port_path: default_port,
+            baud_rate: BAUD_RATE,
+            transport: TransportKind::default(),
+            tcp_address: String::new(),
+            raw_capture: false,
+            connected_endpoint: None,
+            new_profile_name: String::new(),
             available_ports,
             selected_pid_axis: PidAxis::Roll,
+            pid_plot_combined: false,
+            selected_pid_term: PidTerm::P,
             auto_scroll_logs: true,
+            log_scroll_anchor: None,
+            manual_send_text: String::new(),
+            manual_send_hex: false,
             uart_sender: None,
+            uart_alive: None,
             viewport_texture_id: None,
             show_pid_tuning: false,
+            pid_send_axes: Vec::new(),
+            marker_text: String::new(),
+            pending_settings_reset: None,
+            settings_undo: None,
+            show_config_summary: false,
+            show_setpoints: true,
+            show_rate_plot: false,
+            show_fft_plot: false,
+            show_artificial_horizon: false,
+            plot_window_secs: None,
+            plot_x_mode: XAxisMode::default(),
+            plot_full_resolution: false,
+            esc_plot_currents: false,
+            angle_unit: AngleUnit::default(),
+            yaw_display: YawDisplay::default(),
+            plot_cursor_x: None,
+            plot_hover_x: None,
+            plot_capture: None,
+            custom_plot_fields: Vec::new(),
+            stepped_plots: HashSet::new(),
+            new_mute_pattern: String::new(),
+            show_log_info: true,
+            show_log_warn: true,
+            show_log_error: true,
+            show_log_sent: true,
         }
     }
 }
 
 impl AppState {
-    pub fn start_uart_thread(&mut self) -> Result<(), String> {
+    /// Default state with the persisted link settings applied
+    pub fn from_settings(settings: &PersistentSettings) -> Self {
+        let defaults = Self::default();
+        Self {
+            port_path: settings.port_path.clone().unwrap_or(defaults.port_path.clone()),
+            baud_rate: settings.baud_rate,
+            transport: settings.transport,
+            tcp_address: settings.tcp_address.clone(),
+            ..defaults
+        }
+    }
+
+    /// Copies the link selection into the persisted settings when it changed
+    pub fn store_link_settings(&self, settings: &mut PersistentSettings) {
+        if settings.port_path.as_ref() != Some(&self.port_path)
+            || settings.baud_rate != self.baud_rate
+            || settings.transport != self.transport
+            || settings.tcp_address != self.tcp_address
+        {
+            settings.port_path = Some(self.port_path.clone());
+            settings.baud_rate = self.baud_rate;
+            settings.transport = self.transport;
+            settings.tcp_address = self.tcp_address.clone();
+        }
+    }
+
+    /// Copies ingestion-related settings into the data and log buffers used by the UART thread
+    pub fn sync_buffer_settings(&self, persistent_settings: &PersistentSettings) {
+        if let Ok(mut log) = self.log_buffer.lock() {
+            log.set_mute_patterns(&persistent_settings.log_mute_patterns);
+            log.set_log_limit(persistent_settings.log_scrollback);
+            log.set_flight_log(persistent_settings.flight_log_config());
+        }
+        if let Ok(mut buffer) = self.data_buffer.lock() {
+            buffer.set_full_policy(persistent_settings.buffer_full_policy);
+            buffer.rebase_on_rewind = persistent_settings.rebase_on_timestamp_rewind;
+            buffer.detect_gaps = persistent_settings.detect_timestamp_gaps;
+            buffer.set_log_rules(&persistent_settings.log_rules);
+            buffer.set_deviation_alarm(persistent_settings.deviation_alarm());
+            buffer.set_oscillation_detector(persistent_settings.oscillation_detector());
+            buffer.set_vibration_threshold(
+                persistent_settings
+                    .vibration_alarm_enabled
+                    .then_some(persistent_settings.vibration_threshold as f64),
+            );
+        }
+    }
+
+    /// Starts the UART thread; whether the link opened shows up in `connection_state`
+    pub fn start_uart_thread(&mut self) {
         if self.serial_connected {
-            return Ok(());
+            return;
         }
 
-        let port_path = self.port_path.clone();
+        let endpoint = self.endpoint();
         let data_buffer = Arc::clone(&self.data_buffer);
 
-        match uart::start_uart_thread(port_path, data_buffer) {
-            Ok(sender) => {
-                self.uart_sender = Some(sender);
-                self.serial_connected = true;
-                Ok(())
-            }
-            Err(e) => {
-                self.serial_connected = false;
-                self.uart_sender = None;
-                Err(e)
-            }
+        let connection_state = Arc::clone(&self.connection_state);
+
+        let status = self.status.sender("UART");
+        let thread = uart::start_uart_thread(endpoint.clone(), data_buffer, connection_state, status);
+        if self.raw_capture {
+            let _ = thread.sender.send(UartCommand::StartCapture { dir: raw_capture_dir() });
+        }
+        self.uart_sender = Some(thread.sender);
+        self.uart_alive = Some(thread.alive);
+        self.serial_connected = true;
+        self.connected_endpoint = Some(endpoint);
+    }
+
+    pub fn log_level_visible(&self, level: LogLevel) -> bool {
+        match level {
+            LogLevel::Info => self.show_log_info,
+            LogLevel::Warn => self.show_log_warn,
+            LogLevel::Error => self.show_log_error,
+            LogLevel::Sent => self.show_log_sent,
+        }
+    }
+
+    /// Link selected in the connection panel
+    pub fn endpoint(&self) -> Endpoint {
+        match self.transport {
+            TransportKind::Serial => Endpoint::Serial {
+                port_path: self.port_path.clone(),
+                baud_rate: self.baud_rate,
+            },
+            TransportKind::Tcp => Endpoint::Tcp {
+                address: self.tcp_address.clone(),
+            },
+        }
+    }
+
+    /// True when connected but the selected link differs from the open one
+    pub fn port_changed_since_connect(&self) -> bool {
+        self.connected_endpoint
+            .as_ref()
+            .is_some_and(|endpoint| *endpoint != self.endpoint())
+    }
+
+    /// Selects a saved profile's port and baud rate
+    pub fn apply_profile(&mut self, profile: &ConnectionProfile) {
+        self.transport = TransportKind::Serial;
+        self.port_path = profile.port_path.clone();
+        self.baud_rate = profile.baud_rate;
+    }
+
+    /// Closes the current connection and reopens with the current settings
+    pub fn reconnect_uart(&mut self) {
+        self.disconnect_uart();
+        self.start_uart_thread();
+    }
+
+    /// Starts or stops the raw byte capture, applying it to the open link if any
+    pub fn set_raw_capture(&mut self, enabled: bool) {
+        self.raw_capture = enabled;
+        if let Some(sender) = &self.uart_sender {
+            let command = if enabled {
+                UartCommand::StartCapture { dir: raw_capture_dir() }
+            } else {
+                UartCommand::StopCapture
+            };
+            let _ = sender.send(command);
         }
     }
 
@@ -121,10 +387,83 @@ impl AppState {
         if let Some(sender) = &self.uart_sender {
             let _ = sender.send(UartCommand::Disconnect);
         }
+        self.forget_uart_thread();
+        if let Ok(mut connection_state) = self.connection_state.lock() {
+            *connection_state = ConnectionState::Disconnected;
+        }
+    }
+
+    /// True when a UART thread was started and has since exited on its own, e.g. the
+    /// port failed to open or the TCP bridge closed the connection
+    pub fn uart_thread_exited(&self) -> bool {
+        self.uart_alive.as_ref().is_some_and(|alive| !alive.load(Ordering::Acquire))
+    }
+
+    fn forget_uart_thread(&mut self) {
         self.uart_sender = None;
+        self.uart_alive = None;
         self.serial_connected = false;
+        self.connected_endpoint = None;
+        if let Ok(mut buffer) = self.data_buffer.lock() {
+            buffer.commands.clear();
+        }
+    }
+
+}
+
+/// Screenshots the window once a requested plot has been drawn, and saves its area as PNG
+pub fn plot_export_system(
+    mut commands: Commands,
+    mut state: ResMut<AppState>,
+    settings: Res<PersistentSettings>,
+) {
+    let Some(PlotCapture { plot_id, rect: Some(rect) }) = state.plot_capture else {
+        return;
+    };
+    state.plot_capture = None;
+
+    let width = settings.plot_export_width;
+    let data_buffer = Arc::clone(&state.data_buffer);
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(move |trigger: Trigger<ScreenshotCaptured>| {
+            let message = match export::save_plot_png(trigger.event().0.clone(), rect, width, plot_id) {
+                Ok(path) => format!("Plot saved to {}", path.display()),
+                Err(e) => format!("Plot export failed: {}", e),
+            };
+            if let Ok(mut buffer) = data_buffer.lock() {
+                buffer.push_log(message);
+            }
+        });
+}
+
+/// Directory raw byte captures are written to
+pub fn raw_capture_dir() -> std::path::PathBuf {
+    crate::persistence::app_config_dir().join("captures")
+}
+
+/// Opens the saved link at startup when auto-connect is on. A missing device is
+/// logged by the UART thread and the app ends up disconnected.
+pub fn auto_connect_system(mut state: ResMut<AppState>, settings: Res<PersistentSettings>) {
+    if !settings.auto_connect_on_launch {
+        return;
     }
+    if settings.transport == TransportKind::Serial && settings.port_path.is_none() {
+        if let Ok(mut buffer) = state.data_buffer.lock() {
+            buffer.push_log_level(LogLevel::Warn, "Auto-connect skipped: no saved serial port".to_string());
+        }
+        return;
+    }
+
+    state.start_uart_thread();
+}
 
+/// Marks the link closed once the UART thread has exited, keeping its error on the status
+/// dot, so the connect-time pushes run again on the next connect
+pub fn uart_exit_system(mut state: ResMut<AppState>) {
+    if state.uart_thread_exited() {
+        state.forget_uart_thread();
+    }
 }
 
 /// Sends Disconnect to the UART thread on app exit so the serial port is released cleanly.
@@ -153,10 +492,153 @@ pub fn command_dispatch_system(
     if timer.timer.just_finished()
         && let Some(sender) = &state.uart_sender
     {
-        if let Some(frame) = command_queue.dequeue() {
-            if let Err(e) = sender.send(UartCommand::Send { data: frame }) {
-                eprintln!("Failed to send command: {}", e);
+        if let Some(QueuedCommand { command, batch }) = command_queue.dequeue() {
+            // Old or newer firmware may misread our frames; don't send it any
+            if let Ok(mut buffer) = state.data_buffer.lock()
+                && let Some(version) = buffer.firmware_version.clone()
+                && version.is_incompatible()
+            {
+                buffer.push_log_level(
+                    LogLevel::Error,
+                    format!("Not sent: {} (firmware {} uses another protocol)", command.describe(), version.text),
+                );
+                return;
+            }
+            let frame = command.to_binary_frame();
+            let hex = hex_bytes(&frame);
+            match sender.send(UartCommand::Send { data: frame }) {
+                Ok(()) => {
+                    if let Ok(mut buffer) = state.data_buffer.lock() {
+                        let seq = buffer.commands.record_sent(command, batch);
+                        buffer.push_log_level(
+                            LogLevel::Sent,
+                            format!("→ FC: #{} {} [{}]", seq, command.describe(), hex),
+                        );
+                    }
+                }
+                Err(e) => error!("Failed to send command: {}", e),
+            }
+        }
+    }
+}
+
+/// Warns about commands the firmware has not acknowledged within the timeout, and
+/// about a calibration that never reported "CAL DONE"
+pub fn ack_timeout_system(state: Res<AppState>, settings: Res<PersistentSettings>) {
+    let Ok(mut buffer) = state.data_buffer.lock() else {
+        return;
+    };
+    let timeout = settings.ack_timeout();
+    for command in buffer.commands.expire(timeout) {
+        buffer.push_log_level(
+            LogLevel::Warn,
+            format!(
+                "No ACK for #{} {} after {:.1}s",
+                command.seq,
+                command.command.describe(),
+                timeout.as_secs_f32()
+            ),
+        );
+    }
+    if let Some(batch) = buffer.commands.take_finished_batch()
+        && batch.failed
+    {
+        buffer.push_log_level(
+            LogLevel::Warn,
+            format!(
+                "Send All incomplete: {} of {} commands ACKed; the drone may have a mixed config",
+                batch.acked, batch.expected
+            ),
+        );
+    }
+
+    let calibration_timeout = Duration::from_secs_f32(settings.calibration_timeout_secs.max(1.0));
+    if buffer.commands.expire_calibration(calibration_timeout) {
+        buffer.push_log_level(
+            LogLevel::Warn,
+            format!(
+                "Calibration did not report done within {:.0}s",
+                calibration_timeout.as_secs_f32()
+            ),
+        );
+    }
+}
+
+/// Queues the saved flight config once each time the port opens, so a power-cycled
+/// drone gets its settings back without resending them by hand
+pub fn config_push_on_connect_system(
+    state: Res<AppState>,
+    settings: Res<PersistentSettings>,
+    command_queue: Res<CommandQueue>,
+    mut was_connected: Local<bool>,
+) {
+    let just_connected = state.serial_connected && !*was_connected;
+    *was_connected = state.serial_connected;
+    if !just_connected || !settings.push_config_on_connect {
+        return;
+    }
+
+    let config = settings.to_config_packet();
+    command_queue.enqueue(protocol::CommandType::Config(config));
+    if let Ok(mut buffer) = state.data_buffer.lock() {
+        buffer.push_log(format!("Config pushed: {}", config.summary()));
+    }
+}
+
+/// Asks the firmware for its version each time the port opens, forgetting the
+/// previous link's answer
+pub fn version_query_on_connect_system(state: Res<AppState>, mut was_connected: Local<bool>) {
+    let just_connected = state.serial_connected && !*was_connected;
+    *was_connected = state.serial_connected;
+    if !just_connected {
+        return;
+    }
+    let Some(sender) = &state.uart_sender else {
+        return;
+    };
+
+    if let Ok(mut buffer) = state.data_buffer.lock() {
+        buffer.firmware_version = None;
+    }
+    match sender.send(UartCommand::Send { data: protocol::VERSION_QUERY.to_vec() }) {
+        Ok(()) => {
+            if let Ok(mut buffer) = state.data_buffer.lock() {
+                buffer.push_log_level(LogLevel::Sent, "→ FC: version query".to_string());
             }
         }
+        Err(e) => error!("Failed to send version query: {}", e),
+    }
+}
+
+/// Space-separated uppercase hex, e.g. "A5 05 00 3C"
+pub fn hex_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Periodically resends the flight config so a drone that rebooted mid-session gets it back
+pub fn config_refresh_system(
+    time: Res<Time>,
+    mut refresh: ResMut<ConfigRefreshTimer>,
+    state: Res<AppState>,
+    settings: Res<PersistentSettings>,
+    command_queue: Res<CommandQueue>,
+) {
+    if !state.serial_connected || !settings.config_refresh_enabled {
+        refresh.timer.reset();
+        return;
+    }
+
+    let interval = Duration::from_secs_f32(settings.config_refresh_secs.max(1.0));
+    if refresh.timer.duration() != interval {
+        refresh.timer.set_duration(interval);
+    }
+
+    refresh.timer.tick(time.delta());
+    if refresh.timer.just_finished() {
+        command_queue.enqueue_uncoalesced(protocol::CommandType::Config(settings.to_config_packet()));
     }
 }
@@ -1,7 +1,17 @@
 // Serial port configuration
 pub const BAUD_RATE: u32 = 115_200;
+pub const COMMON_BAUD_RATES: [u32; 6] = [57_600, 115_200, 230_400, 420_000, 460_800, 921_600];
 pub const SERIAL_TIMEOUT_MS: u64 = 100;
+/// Largest single write the GUI allows on the link. Manual sends go out unframed and
+/// are checked against it; command frames are far smaller.
+pub const MAX_SEND_BYTES: usize = 240;
 
 // Data buffer limits
 pub const MAX_POINTS: usize = 2000;
+/// ESC temperature readouts shade from green at COOL to red at HOT (°C)
+pub const ESC_TEMP_COOL_C: f32 = 40.0;
+pub const ESC_TEMP_HOT_C: f32 = 90.0;
+/// Default log panel scrollback
 pub const MAX_LOG_MESSAGES: usize = 100;
+/// Lines and frames kept for the raw packet view
+pub const MAX_RAW_PACKETS: usize = 200;
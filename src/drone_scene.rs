@@ -1,16 +1,24 @@
 // Bevy 3D drone scene
 
 use bevy::asset::RenderAssetUsages;
+use bevy::color::Mix;
 use bevy::prelude::*;
 use bevy::render::camera::RenderTarget;
 use bevy::render::render_resource::{
     Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
 };
 
+use crate::persistence::PersistentSettings;
+use crate::telemetry::TelemetryData;
+
 /// Marker component for the drone entity
 #[derive(Component)]
 pub struct Drone;
 
+/// Marker for the ground grid, hidden when the grid is switched off
+#[derive(Component)]
+pub struct GroundGrid;
+
 /// Marker for the viewport camera
 #[derive(Component)]
 pub struct ViewportCamera;
@@ -21,8 +29,26 @@ pub struct DroneOrientation {
     pub roll: f32,
     pub pitch: f32,
     pub yaw: f32,
+    /// 0.0 = telemetry fresh, 1.0 = stale or none; fades the model toward gray
+    pub staleness: f32,
+    /// Commanded throttle (0.0-1.0); the body glows brighter with it
+    pub throttle: f32,
 }
 
+/// Drone model materials with their live colors, so they can be faded and restored
+#[derive(Resource)]
+pub struct DroneMaterials {
+    materials: Vec<(Handle<StandardMaterial>, Color)>,
+    /// Front marker, whose live color comes from the plot style instead
+    front: Handle<StandardMaterial>,
+    body: Handle<StandardMaterial>,
+}
+
+/// Color the model fades to when telemetry is stale
+const STALE_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+/// Body emissive at full throttle; kept low so the front marker still stands out
+const BODY_GLOW: LinearRgba = LinearRgba::rgb(0.12, 0.12, 0.10);
+
 /// Resource to hold the render target image handle
 #[derive(Resource)]
 pub struct ViewportImage {
@@ -35,16 +61,34 @@ impl Default for DroneOrientation {
             roll: 0.0,
             pitch: 0.0,
             yaw: 0.0,
+            staleness: 1.0,
+            throttle: 0.0,
         }
     }
 }
 
+impl DroneOrientation {
+    /// Takes attitude (radians, as in telemetry) and throttle from a sample
+    pub fn apply_sample(&mut self, sample: &TelemetryData) {
+        self.roll = sample.roll;
+        self.pitch = sample.pitch;
+        self.yaw = sample.yaw;
+        self.throttle = sample.input_throttle;
+    }
+
+    /// Model rotation: yaw about Y, then pitch about X, then roll about Z
+    pub fn rotation(&self) -> Quat {
+        Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, self.roll)
+    }
+}
+
 /// Setup the 3D drone scene
 pub fn setup_drone_scene(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut images: ResMut<Assets<Image>>,
+    settings: Res<PersistentSettings>,
 ) {
     // Create render target image for the viewport
     // Using smaller resolution for better performance on Raspberry Pi
@@ -79,7 +123,7 @@ pub fn setup_drone_scene(
     let body_color = Color::srgb(0.5, 0.5, 0.5);
     let arm_color = Color::srgb(0.4, 0.4, 0.4);
     let motor_color = Color::srgb(0.2, 0.2, 0.2);
-    let front_color = Color::srgb(0.0, 0.8, 0.0);
+    let front_color = settings.plot_style.front_marker_color();
 
     // Create materials
     let body_material = materials.add(StandardMaterial {
@@ -98,6 +142,16 @@ pub fn setup_drone_scene(
         base_color: front_color,
         ..default()
     });
+    commands.insert_resource(DroneMaterials {
+        materials: vec![
+            (body_material.clone(), body_color),
+            (arm_material.clone(), arm_color),
+            (motor_material.clone(), motor_color),
+            (front_material.clone(), front_color),
+        ],
+        front: front_material.clone(),
+        body: body_material.clone(),
+    });
 
     // Parent entity for the entire drone
     let drone_entity = commands
@@ -204,6 +258,7 @@ pub fn setup_drone_scene(
     ));
 
     commands.spawn((
+        GroundGrid,
         Mesh3d(meshes.add(create_grid_mesh(10.0, 20))),
         MeshMaterial3d(materials.add(StandardMaterial {
             base_color: Color::srgba(0.5, 0.5, 0.5, 0.3),
@@ -211,10 +266,13 @@ pub fn setup_drone_scene(
             alpha_mode: AlphaMode::Blend,
             ..default()
         })),
-        Transform::from_xyz(0.0, -0.5, 0.0),
+        Transform::from_xyz(0.0, GROUND_Y, 0.0),
     ));
 }
 
+/// Height of the ground grid, also where the world axes are drawn
+const GROUND_Y: f32 = -0.5;
+
 // Generate grid mesh
 fn create_grid_mesh(size: f32, divisions: usize) -> Mesh {
     let mut positions = Vec::new();
@@ -255,18 +313,134 @@ pub fn update_drone_orientation(
     time: Res<Time>,
 ) {
     for (mut transform, orientation) in query.iter_mut() {
-        // Convert degrees to radians and calculate target rotation
-        let target_rotation = Quat::from_euler(
-            EulerRot::YXZ,
-            orientation.yaw,
-            orientation.pitch,
-            orientation.roll,
-        );
-
-        // Use slerp for smooth interpolation
+        let target_rotation = orientation.rotation();
+
+        // Use slerp for smooth interpolation; it takes the shorter arc, so yaw
+        // crossing ±180° turns the model the short way rather than spinning it round
         // Adjust the interpolation speed (higher = faster, 0.1 = smooth)
         let interpolation_speed = 10.0;
         let t = (interpolation_speed * time.delta_secs()).min(1.0);
         transform.rotation = transform.rotation.slerp(target_rotation, t);
     }
 }
+
+/// System to fade the drone model toward gray as telemetry goes stale
+pub fn update_drone_tint(
+    query: Query<&DroneOrientation, With<Drone>>,
+    drone_materials: Option<Res<DroneMaterials>>,
+    settings: Res<PersistentSettings>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut applied: Local<Option<(f32, [u8; 3])>>,
+) {
+    let (Some(drone_materials), Some(orientation)) = (drone_materials, query.iter().next()) else {
+        return;
+    };
+    // Quantize so the materials are only touched when the tint visibly changes. Settings
+    // change detection can't help: the UI borrows them mutably every frame.
+    let staleness = (orientation.staleness.clamp(0.0, 1.0) * 20.0).round() / 20.0;
+    let front_marker = settings.plot_style.front_marker;
+    if *applied == Some((staleness, front_marker)) {
+        return;
+    }
+    *applied = Some((staleness, front_marker));
+
+    let stale = STALE_COLOR.to_linear();
+    let front_color = settings.plot_style.front_marker_color();
+    for (handle, color) in &drone_materials.materials {
+        let live_color = if *handle == drone_materials.front { &front_color } else { color };
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color = live_color.to_linear().mix(&stale, staleness).into();
+        }
+    }
+}
+
+/// System to brighten the drone body with commanded throttle
+pub fn update_body_glow(
+    query: Query<&DroneOrientation, With<Drone>>,
+    drone_materials: Option<Res<DroneMaterials>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut applied: Local<Option<f32>>,
+) {
+    let (Some(drone_materials), Some(orientation)) = (drone_materials, query.iter().next()) else {
+        return;
+    };
+    // No glow once telemetry has gone stale, and only touch the material on visible steps
+    let level = orientation.throttle.clamp(0.0, 1.0) * (1.0 - orientation.staleness.clamp(0.0, 1.0));
+    let level = (level * 20.0).round() / 20.0;
+    if *applied == Some(level) {
+        return;
+    }
+    *applied = Some(level);
+
+    if let Some(material) = materials.get_mut(&drone_materials.body) {
+        material.emissive = BODY_GLOW * level;
+    }
+}
+
+/// Applies the grid toggle and draws the world axes (X red, Y green, Z blue) when enabled
+pub fn update_scene_helpers(
+    settings: Res<PersistentSettings>,
+    mut grid_query: Query<&mut Visibility, With<GroundGrid>>,
+    mut gizmos: Gizmos,
+) {
+    let grid_visibility = if settings.show_grid {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut visibility in grid_query.iter_mut() {
+        if *visibility != grid_visibility {
+            *visibility = grid_visibility;
+        }
+    }
+
+    if settings.show_axes {
+        gizmos.axes(Transform::from_xyz(0.0, GROUND_Y, 0.0), 0.5);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::TelemetryPacket;
+    use std::f32::consts::FRAC_PI_2;
+
+    /// Telemetry frame payload with only roll, pitch and yaw (radians) set
+    fn attitude_payload(roll: f32, pitch: f32, yaw: f32) -> Vec<u8> {
+        let mut payload = vec![0u8; TelemetryPacket::LEN];
+        // Layout: timestamp_ms (u32), roll, pitch, yaw (f32), little-endian
+        payload[4..8].copy_from_slice(&roll.to_le_bytes());
+        payload[8..12].copy_from_slice(&pitch.to_le_bytes());
+        payload[12..16].copy_from_slice(&yaw.to_le_bytes());
+        payload
+    }
+
+    fn model_rotation(payload: &[u8]) -> Quat {
+        let sample = TelemetryData::from_payload(payload).expect("payload decodes");
+        let mut orientation = DroneOrientation::default();
+        orientation.apply_sample(&sample);
+        orientation.rotation()
+    }
+
+    #[test]
+    fn roll_turns_the_model_about_z() {
+        let rotation = model_rotation(&attitude_payload(FRAC_PI_2, 0.0, 0.0));
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_z(FRAC_PI_2), 1e-6));
+    }
+
+    #[test]
+    fn pitch_and_yaw_use_radians() {
+        let rotation = model_rotation(&attitude_payload(0.0, 0.3, 0.0));
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_x(0.3), 1e-6));
+        let rotation = model_rotation(&attitude_payload(0.0, 0.0, -FRAC_PI_2));
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_y(-FRAC_PI_2), 1e-6));
+    }
+
+    #[test]
+    fn yaw_is_applied_before_pitch_and_roll() {
+        let (roll, pitch, yaw) = (0.2, -0.4, 1.0);
+        let rotation = model_rotation(&attitude_payload(roll, pitch, yaw));
+        let expected = Quat::from_rotation_y(yaw) * Quat::from_rotation_x(pitch) * Quat::from_rotation_z(roll);
+        assert!(rotation.abs_diff_eq(expected, 1e-6));
+    }
+}
@@ -0,0 +1,227 @@
+// CSV export of the telemetry buffer with a self-describing session header,
+// one-file JSON bug reports, and PNG snapshots of plots
+
+use bevy::image::Image;
+use bevy_egui::egui;
+use chrono::Local;
+use image::imageops::FilterType;
+use serde_json::json;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::log_buffer::LogBuffer;
+use crate::persistence::{self, PersistentSettings};
+use crate::telemetry::{DataBuffer, TelemetryData};
+
+/// Connection and flight-config context written at the top of every export
+pub struct SessionMetadata {
+    pub port_path: String,
+    pub baud_rate: u32,
+    pub settings: PersistentSettings,
+}
+
+impl SessionMetadata {
+    pub fn new(port_path: &str, baud_rate: u32, settings: &PersistentSettings) -> Self {
+        Self {
+            port_path: port_path.to_string(),
+            baud_rate,
+            settings: settings.clone(),
+        }
+    }
+
+    /// Header lines, each prefixed with `# ` so CSV readers can skip them
+    fn header_lines(&self, buffer: &DataBuffer) -> Vec<String> {
+        let s = &self.settings;
+        vec![
+            format!("session_id: {}", buffer.session.id),
+            format!("session_start: {}", buffer.session.started.to_rfc3339()),
+            format!("exported: {}", Local::now().to_rfc3339()),
+            format!("port: {} @ {} baud", self.port_path, self.baud_rate),
+            format!(
+                "config: throttle_hover={} throttle_expo={} max_roll_angle={} max_pitch_angle={} max_yaw_rate={}",
+                s.throttle_hover, s.throttle_expo, s.max_roll_angle, s.max_pitch_angle, s.max_yaw_rate
+            ),
+            format!("samples: {}", buffer.data.len()),
+            "units: roll/pitch/yaw/input_* rad, gyro_* rad/s, height m".to_string(),
+        ]
+    }
+}
+
+/// Default directory for exported files
+pub fn export_dir() -> PathBuf {
+    persistence::app_config_dir().join("exports")
+}
+
+/// Writes the buffer to a timestamped CSV file in `export_dir()` and returns its path
+pub fn export_csv(buffer: &DataBuffer, metadata: &SessionMetadata) -> Result<PathBuf, String> {
+    let dir = export_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create {:?}: {}", dir, e))?;
+    let path = dir.join(format!(
+        "telemetry_{}.csv",
+        Local::now().format("%Y%m%d_%H%M%S")
+    ));
+    write_csv(&path, buffer, metadata)?;
+    Ok(path)
+}
+
+fn write_csv(path: &Path, buffer: &DataBuffer, metadata: &SessionMetadata) -> Result<(), String> {
+    let file = fs::File::create(path).map_err(|e| format!("failed to create {:?}: {}", path, e))?;
+    let mut w = BufWriter::new(file);
+    let io_err = |e: std::io::Error| format!("failed to write {:?}: {}", path, e);
+
+    for line in metadata.header_lines(buffer) {
+        writeln!(w, "# {}", line).map_err(io_err)?;
+    }
+    writeln!(w, "{}", csv_header()).map_err(io_err)?;
+    // Each marker goes on the first sample at or after its time
+    let mut events = buffer.events.iter().peekable();
+    for d in &buffer.data {
+        let time = d.timestamp as f64 / 1000.0;
+        let mut labels = Vec::new();
+        while let Some((_, label)) = events.next_if(|(t, _)| *t <= time) {
+            labels.push(label.as_str());
+        }
+        writeln!(w, "{},{}", csv_row(d), csv_text(&labels.join("; "))).map_err(io_err)?;
+    }
+    w.flush().map_err(io_err)
+}
+
+/// Column names of the telemetry CSV, matching `csv_row` plus the trailing event column
+pub fn csv_header() -> String {
+    format!("clock_time,timestamp_ms,{},lat,lon,sats,event", TelemetryData::FIELD_NAMES.join(","))
+}
+
+/// One sample as CSV, without the event column
+pub fn csv_row(d: &TelemetryData) -> String {
+    let mut row = format!("{},{}", d.clock_time.format("%H:%M:%S%.3f"), d.timestamp);
+    for name in TelemetryData::FIELD_NAMES {
+        row.push(',');
+        // Fields are f32 on the wire; format them as such to avoid widening noise
+        row.push_str(&(d.field(name).unwrap_or_default() as f32).to_string());
+    }
+    // GPS columns stay empty for samples without a fix
+    let coord = |c: Option<f64>| c.map(|v| format!("{:.7}", v)).unwrap_or_default();
+    row.push_str(&format!(",{},{},{}", coord(d.lat), coord(d.lon), d.sats));
+    row
+}
+
+/// Quotes a free-text CSV field when it contains a separator, quote or newline
+fn csv_text(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Number of most recent telemetry samples included in a bug report
+const BUG_REPORT_SAMPLES: usize = 50;
+
+/// Writes settings, environment, connection status, recent logs, parse errors and
+/// the latest telemetry samples to a single timestamped JSON file in `export_dir()`
+pub fn export_bug_report(
+    buffer: &DataBuffer,
+    log: &LogBuffer,
+    metadata: &SessionMetadata,
+    connection: &str,
+) -> Result<PathBuf, String> {
+    let logs: Vec<_> = log
+        .logs
+        .iter()
+        .map(|l| {
+            json!({
+                "time": l.clock_time.to_rfc3339(),
+                "level": l.level.label(),
+                "message": l.message,
+            })
+        })
+        .collect();
+    let samples: Vec<_> = buffer
+        .data
+        .iter()
+        .skip(buffer.data.len().saturating_sub(BUG_REPORT_SAMPLES))
+        .map(|d| {
+            let mut sample = serde_json::Map::new();
+            sample.insert("clock_time".into(), json!(d.clock_time.to_rfc3339()));
+            sample.insert("timestamp_ms".into(), json!(d.timestamp));
+            for name in TelemetryData::FIELD_NAMES {
+                sample.insert(name.into(), json!(d.field(name).unwrap_or_default() as f32));
+            }
+            sample.insert("lat".into(), json!(d.lat));
+            sample.insert("lon".into(), json!(d.lon));
+            sample.insert("sats".into(), json!(d.sats));
+            serde_json::Value::Object(sample)
+        })
+        .collect();
+
+    let report = json!({
+        "generated": Local::now().to_rfc3339(),
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "environment": {
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+        },
+        "session": {
+            "id": buffer.session.id.to_string(),
+            "started": buffer.session.started.to_rfc3339(),
+        },
+        "connection": {
+            "port": metadata.port_path,
+            "baud_rate": metadata.baud_rate,
+            "state": connection,
+            "telemetry_rate_hz": buffer.telemetry_rate_hz(),
+            "last_sample_age_secs": buffer.last_sample_at.map(|t| t.elapsed().as_secs_f32()),
+        },
+        "diagnostics": {
+            "samples_buffered": buffer.data.len(),
+            "downsample_stride": buffer.downsample_stride,
+            "timestamp_rewinds": buffer.timestamp_rewinds,
+            "parse_error_count": buffer.parse_error_count,
+            "recent_parse_errors": buffer.recent_parse_errors,
+            "muted_log_count": log.muted_count,
+            "vibration_deg_s": buffer.vibration,
+        },
+        "settings": metadata.settings,
+        "logs": logs,
+        "recent_telemetry": samples,
+    });
+
+    let dir = export_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create {:?}: {}", dir, e))?;
+    let path = dir.join(format!(
+        "bug_report_{}.json",
+        Local::now().format("%Y%m%d_%H%M%S")
+    ));
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("failed to serialize bug report: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("failed to write {:?}: {}", path, e))?;
+    Ok(path)
+}
+
+/// Crops a window screenshot to `rect` (physical pixels), scales it to `width` pixels
+/// wide and writes it to a timestamped PNG in `export_dir()`
+pub fn save_plot_png(screenshot: Image, rect: egui::Rect, width: u32, plot_id: &str) -> Result<PathBuf, String> {
+    let image = screenshot
+        .try_into_dynamic()
+        .map_err(|e| format!("unsupported screenshot format: {:?}", e))?;
+    let x = rect.min.x.max(0.0) as u32;
+    let y = rect.min.y.max(0.0) as u32;
+    let crop_w = (rect.width() as u32).min(image.width().saturating_sub(x));
+    let crop_h = (rect.height() as u32).min(image.height().saturating_sub(y));
+    if crop_w == 0 || crop_h == 0 {
+        return Err("plot was not on screen".to_string());
+    }
+
+    let height = (crop_h as f32 * width as f32 / crop_w as f32).round().max(1.0) as u32;
+    let plot = image
+        .crop_imm(x, y, crop_w, crop_h)
+        .resize_exact(width, height, FilterType::Lanczos3)
+        .to_rgb8();
+
+    let dir = export_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("failed to create {:?}: {}", dir, e))?;
+    let path = dir.join(format!("{}_{}.png", plot_id, Local::now().format("%Y%m%d_%H%M%S")));
+    plot.save(&path).map_err(|e| format!("failed to write {:?}: {}", path, e))?;
+    Ok(path)
+}
@@ -0,0 +1,101 @@
+// Rolling text copy of the log panel on disk
+
+use bevy::log::error;
+use chrono::{DateTime, Local};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const FILE_NAME: &str = "flight.log";
+/// Lines are flushed to disk at most this often, and when the log is closed
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlightLogConfig {
+    pub dir: PathBuf,
+    pub max_bytes: u64,
+    /// Number of rotated files kept besides the active one
+    pub keep: usize,
+    pub include_frame_errors: bool,
+}
+
+pub struct FlightLog {
+    pub config: FlightLogConfig,
+    writer: BufWriter<File>,
+    written: u64,
+    last_flush: Instant,
+}
+
+impl FlightLog {
+    pub fn open(config: FlightLogConfig) -> Result<Self, String> {
+        fs::create_dir_all(&config.dir)
+            .map_err(|e| format!("failed to create log dir {:?}: {}", config.dir, e))?;
+        let (writer, written) = open_active(&config.dir)?;
+        Ok(Self {
+            config,
+            writer,
+            written,
+            last_flush: Instant::now(),
+        })
+    }
+
+    /// Appends a line using the same timestamp format as the log panel
+    pub fn write_line(&mut self, clock_time: &DateTime<Local>, message: &str) {
+        let line = format!("[{}] {}\n", clock_time.format("%H:%M:%S%.3f"), message);
+        if self.written + line.len() as u64 > self.config.max_bytes
+            && let Err(e) = self.rotate()
+        {
+            error!("Flight log rotation failed: {}", e);
+        }
+        if self.writer.write_all(line.as_bytes()).is_ok() {
+            self.written += line.len() as u64;
+        }
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            let _ = self.writer.flush();
+            self.last_flush = Instant::now();
+        }
+    }
+
+    fn rotate(&mut self) -> Result<(), String> {
+        let _ = self.writer.flush();
+        let dir = &self.config.dir;
+
+        if self.config.keep == 0 {
+            let _ = fs::remove_file(dir.join(FILE_NAME));
+        } else {
+            let _ = fs::remove_file(rotated_path(dir, self.config.keep));
+            for n in (1..self.config.keep).rev() {
+                let _ = fs::rename(rotated_path(dir, n), rotated_path(dir, n + 1));
+            }
+            fs::rename(dir.join(FILE_NAME), rotated_path(dir, 1))
+                .map_err(|e| format!("failed to rotate {}: {}", FILE_NAME, e))?;
+        }
+
+        let (writer, written) = open_active(dir)?;
+        self.writer = writer;
+        self.written = written;
+        Ok(())
+    }
+}
+
+impl Drop for FlightLog {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+fn rotated_path(dir: &Path, n: usize) -> PathBuf {
+    dir.join(format!("{}.{}", FILE_NAME, n))
+}
+
+fn open_active(dir: &Path) -> Result<(BufWriter<File>, u64), String> {
+    let path = dir.join(FILE_NAME);
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("failed to open {:?}: {}", path, e))?;
+    let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+    Ok((BufWriter::new(file), written))
+}
@@ -0,0 +1,129 @@
+// Window-less logging: opens the configured link and streams telemetry to a CSV file
+
+use chrono::Local;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::app::AppState;
+use crate::export;
+use crate::persistence::PersistentSettings;
+use crate::telemetry::TelemetryData;
+use crate::uart::ConnectionState;
+
+/// How often new samples and log lines are collected from the UART thread
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A link that stays in the error state this long is given up on
+const LINK_ERROR_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs until the process is killed or the link fails, which returns an error so a
+/// supervisor can restart it. Uses the link and ingestion settings saved by the GUI;
+/// log panel messages are echoed to stderr. Every received sample is written, whatever
+/// the buffer-full policy keeps for plotting.
+pub fn run(settings: &PersistentSettings, log_file: &Path) -> Result<(), String> {
+    let mut state = AppState::from_settings(settings);
+    state.sync_buffer_settings(settings);
+
+    let file = File::create(log_file).map_err(|e| format!("failed to create {:?}: {}", log_file, e))?;
+    let mut w = BufWriter::new(file);
+    let io_err = |e: std::io::Error| format!("failed to write {:?}: {}", log_file, e);
+    writeln!(w, "# headless log started {}", Local::now().to_rfc3339()).map_err(io_err)?;
+    writeln!(w, "# link: {}", state.endpoint().describe()).map_err(io_err)?;
+    writeln!(w, "{}", export::csv_header()).map_err(io_err)?;
+
+    if let Ok(mut buffer) = state.data_buffer.lock() {
+        buffer.start_recording();
+    }
+    state.start_uart_thread();
+    eprintln!("Logging {} to {}", state.endpoint().describe(), log_file.display());
+
+    let mut next_log_seq = 0;
+    let mut last_state: Option<ConnectionState> = None;
+    let mut failing_since: Option<Instant> = None;
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let samples = state
+            .data_buffer
+            .lock()
+            .map(|mut buffer| buffer.take_recorded())
+            .unwrap_or_default();
+        write_samples(&mut w, &samples).map_err(io_err)?;
+        w.flush().map_err(io_err)?;
+
+        if let Ok(mut log) = state.log_buffer.lock() {
+            log.drain_pending();
+            state.status.drain_into(&mut log);
+            let from = next_log_seq;
+            for msg in log.logs.iter().filter(|m| m.seq >= from) {
+                eprintln!("[{}] {}", msg.clock_time.format("%H:%M:%S%.3f"), msg.message);
+                next_log_seq = msg.seq + 1;
+            }
+        }
+
+        let current = state.connection_state.lock().ok().map(|s| s.clone());
+        if current != last_state {
+            if let Some(s) = &current {
+                eprintln!("Link: {}", s.describe());
+            }
+            last_state = current;
+        }
+
+        if let Some(ConnectionState::Error(e)) = &last_state {
+            let since = *failing_since.get_or_insert_with(Instant::now);
+            if state.uart_thread_exited() || since.elapsed() >= LINK_ERROR_TIMEOUT {
+                return Err(format!("link failed: {}", e));
+            }
+        } else {
+            failing_since = None;
+        }
+    }
+}
+
+/// One CSV row per sample
+fn write_samples(w: &mut impl Write, samples: &[TelemetryData]) -> std::io::Result<()> {
+    for d in samples {
+        writeln!(w, "{},", export::csv_row(d))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MAX_POINTS;
+    use crate::log_buffer::LogBuffer;
+    use crate::telemetry::{BufferFullPolicy, DataBuffer, TelemetryPacket};
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn writes_every_sample_past_a_full_buffer() {
+        for policy in BufferFullPolicy::ALL {
+            let mut buffer = DataBuffer::new(LogBuffer::new().sender());
+            buffer.set_full_policy(policy);
+            buffer.start_recording();
+            let mut out = Vec::new();
+            let total = MAX_POINTS * 3;
+            for i in 0..total {
+                let mut sample = TelemetryData::from(&TelemetryPacket::zeroed());
+                sample.timestamp = i as u32 * 10;
+                buffer.push(sample);
+                if i % 100 == 99 {
+                    write_samples(&mut out, &buffer.take_recorded()).unwrap();
+                }
+            }
+            write_samples(&mut out, &buffer.take_recorded()).unwrap();
+
+            let rows: Vec<&str> = std::str::from_utf8(&out).unwrap().lines().collect();
+            assert_eq!(rows.len(), total, "{:?}", policy);
+            assert!(buffer.data.len() <= MAX_POINTS);
+            for (i, row) in rows.iter().enumerate() {
+                let timestamp: u32 = row.split(',').nth(1).unwrap().parse().unwrap();
+                assert_eq!(timestamp, i as u32 * 10, "{:?}", policy);
+            }
+        }
+    }
+}
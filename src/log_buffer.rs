@@ -0,0 +1,233 @@
+// Log panel contents, kept behind their own lock so the log view and the plots
+// don't contend for the telemetry buffer each frame.
+
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::sync::mpsc;
+
+use crate::config::{MAX_LOG_MESSAGES, MAX_RAW_PACKETS};
+use crate::flight_log::{FlightLog, FlightLogConfig};
+use crate::telemetry::{LogLevel, LogMessage, RawPacket};
+
+/// A log panel update made while the telemetry lock is held
+pub enum PendingLog {
+    Message {
+        clock_time: DateTime<Local>,
+        level: LogLevel,
+        message: String,
+    },
+    FrameError {
+        clock_time: DateTime<Local>,
+        message: String,
+    },
+    Raw(RawPacket),
+}
+
+/// Queues updates for the log buffer without taking its lock. The telemetry buffer
+/// logs through this, so the UART thread never waits on the log lock, or a flight log
+/// write, while holding the telemetry lock. Applied by `LogBuffer::drain_pending`.
+#[derive(Clone)]
+pub struct LogSender(mpsc::Sender<PendingLog>);
+
+impl LogSender {
+    pub fn send(&self, entry: PendingLog) {
+        // The receiver lives in the log buffer; a failed send only happens at shutdown
+        let _ = self.0.send(entry);
+    }
+}
+
+pub struct LogBuffer {
+    pub logs: VecDeque<LogMessage>,
+    /// Scrollback: oldest log messages are dropped beyond this many
+    log_limit: usize,
+    next_log_seq: u64,
+    pub muted_count: usize,
+    mute_patterns: Vec<String>,
+    flight_log: Option<FlightLog>,
+    /// Config that last failed to open, not retried until the config changes
+    failed_flight_log: Option<FlightLogConfig>,
+    /// Most recent lines and frames as received, oldest first
+    pub raw_packets: VecDeque<RawPacket>,
+    start_time: std::time::Instant,
+    pending_tx: mpsc::Sender<PendingLog>,
+    pending: mpsc::Receiver<PendingLog>,
+}
+
+impl LogBuffer {
+    pub fn new() -> Self {
+        let (pending_tx, pending) = mpsc::channel();
+        Self {
+            logs: VecDeque::with_capacity(MAX_LOG_MESSAGES),
+            log_limit: MAX_LOG_MESSAGES,
+            next_log_seq: 0,
+            muted_count: 0,
+            mute_patterns: Vec::new(),
+            flight_log: None,
+            failed_flight_log: None,
+            raw_packets: VecDeque::with_capacity(MAX_RAW_PACKETS),
+            start_time: std::time::Instant::now(),
+            pending_tx,
+            pending,
+        }
+    }
+
+    /// Handle for queueing log updates from the telemetry buffer
+    pub fn sender(&self) -> LogSender {
+        LogSender(self.pending_tx.clone())
+    }
+
+    /// Applies every update queued through a `LogSender`, oldest first
+    pub fn drain_pending(&mut self) {
+        while let Ok(entry) = self.pending.try_recv() {
+            match entry {
+                PendingLog::Message { clock_time, level, message } => self.push_at(clock_time, level, message),
+                PendingLog::FrameError { clock_time, message } => self.write_frame_error(&clock_time, &message),
+                PendingLog::Raw(packet) => self.record_raw(packet),
+            }
+        }
+    }
+
+    /// Empties the log panel, its muted-line count and the raw packet view
+    pub fn clear(&mut self) {
+        self.logs.clear();
+        self.muted_count = 0;
+        self.raw_packets.clear();
+    }
+
+    pub fn set_log_limit(&mut self, limit: usize) {
+        self.log_limit = limit.max(1);
+        while self.logs.len() > self.log_limit {
+            self.logs.pop_front();
+        }
+    }
+
+    /// Sets the substrings used to drop incoming log lines.
+    pub fn set_mute_patterns(&mut self, patterns: &[String]) {
+        if self.mute_patterns != patterns {
+            self.mute_patterns = patterns.to_vec();
+        }
+    }
+
+    /// Opens, reconfigures or closes the on-disk flight log. Called every frame, so a
+    /// config that failed to open is reported once and only retried after it changes.
+    pub fn set_flight_log(&mut self, config: Option<FlightLogConfig>) {
+        let current = self
+            .flight_log
+            .as_ref()
+            .map(|log| &log.config)
+            .or(self.failed_flight_log.as_ref());
+        if current == config.as_ref() {
+            return;
+        }
+
+        self.flight_log = None;
+        self.failed_flight_log = None;
+        if let Some(config) = config {
+            match FlightLog::open(config.clone()) {
+                Ok(log) => self.flight_log = Some(log),
+                Err(e) => {
+                    self.failed_flight_log = Some(config);
+                    self.push_log(format!("Flight log disabled: {}", e));
+                }
+            }
+        }
+    }
+
+    pub fn record_raw(&mut self, packet: RawPacket) {
+        if self.raw_packets.len() >= MAX_RAW_PACKETS {
+            self.raw_packets.pop_front();
+        }
+        self.raw_packets.push_back(packet);
+    }
+
+    /// Copies a parse error to the flight log when it is set to include them
+    pub fn write_frame_error(&mut self, time: &DateTime<Local>, message: &str) {
+        if let Some(log) = &mut self.flight_log
+            && log.config.include_frame_errors
+        {
+            log.write_line(time, message);
+        }
+    }
+
+    fn is_muted(&self, message: &str) -> bool {
+        self.mute_patterns
+            .iter()
+            .any(|p| !p.is_empty() && message.contains(p.as_str()))
+    }
+
+    pub fn push_log(&mut self, message: String) {
+        self.push_log_level(LogLevel::Info, message);
+    }
+
+    pub fn push_log_level(&mut self, level: LogLevel, message: String) {
+        self.push_at(Local::now(), level, message);
+    }
+
+    fn push_at(&mut self, clock_time: DateTime<Local>, level: LogLevel, message: String) {
+        if self.is_muted(&message) {
+            self.muted_count += 1;
+            return;
+        }
+
+        let log_msg = LogMessage {
+            seq: self.next_log_seq,
+            _timestamp: self.start_time.elapsed().as_secs_f64(),
+            clock_time,
+            level,
+            message,
+        };
+
+        if let Some(log) = &mut self.flight_log {
+            match level {
+                LogLevel::Info => log.write_line(&log_msg.clock_time, &log_msg.message),
+                _ => log.write_line(
+                    &log_msg.clock_time,
+                    &format!("{}: {}", level.label().to_uppercase(), log_msg.message),
+                ),
+            }
+        }
+
+        self.next_log_seq += 1;
+        while self.logs.len() >= self.log_limit {
+            self.logs.pop_front();
+        }
+        self.logs.push_back(log_msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_in(dir: std::path::PathBuf) -> FlightLogConfig {
+        FlightLogConfig {
+            dir,
+            max_bytes: 1024,
+            keep: 1,
+            include_frame_errors: false,
+        }
+    }
+
+    #[test]
+    fn failed_flight_log_is_reported_once_per_config() {
+        // A regular file where the log directory should be makes the open fail
+        let blocker = std::env::temp_dir().join(format!("drone_gui_flight_log_{}", std::process::id()));
+        std::fs::write(&blocker, b"").unwrap();
+        let failing = config_in(blocker.join("logs"));
+        let failing_too = config_in(blocker.join("other"));
+
+        let mut log = LogBuffer::new();
+        for _ in 0..5 {
+            log.set_flight_log(Some(failing.clone()));
+        }
+        assert_eq!(log.logs.len(), 1);
+
+        log.set_flight_log(Some(failing_too));
+        assert_eq!(log.logs.len(), 2);
+        log.set_flight_log(None);
+        log.set_flight_log(Some(failing));
+        assert_eq!(log.logs.len(), 3);
+
+        std::fs::remove_file(&blocker).unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+// User-defined rules that turn telemetry conditions into log entries
+
+use serde::{Deserialize, Serialize};
+
+use crate::telemetry::TelemetryData;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleCondition {
+    /// Value goes from below to at/above the threshold
+    #[default]
+    CrossesAbove,
+    /// Value goes from above to at/below the threshold
+    CrossesBelow,
+    /// Value changes by more than the threshold between consecutive samples
+    Changes,
+}
+
+impl RuleCondition {
+    pub const ALL: [RuleCondition; 3] = [
+        RuleCondition::CrossesAbove,
+        RuleCondition::CrossesBelow,
+        RuleCondition::Changes,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RuleCondition::CrossesAbove => "crosses above",
+            RuleCondition::CrossesBelow => "crosses below",
+            RuleCondition::Changes => "changes by more than",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogRule {
+    pub enabled: bool,
+    /// Name from `TelemetryData::FIELD_NAMES`
+    pub field: String,
+    pub condition: RuleCondition,
+    pub threshold: f32,
+}
+
+impl Default for LogRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            field: "height".to_string(),
+            condition: RuleCondition::CrossesAbove,
+            threshold: 0.0,
+        }
+    }
+}
+
+impl LogRule {
+    /// Returns the log message if the rule fires between two consecutive samples
+    pub fn evaluate(&self, prev: &TelemetryData, cur: &TelemetryData) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        let before = prev.field(&self.field)?;
+        let after = cur.field(&self.field)?;
+        let threshold = self.threshold as f64;
+
+        let fired = match self.condition {
+            RuleCondition::CrossesAbove => before < threshold && after >= threshold,
+            RuleCondition::CrossesBelow => before > threshold && after <= threshold,
+            RuleCondition::Changes => (after - before).abs() > threshold,
+        };
+
+        fired.then(|| {
+            format!(
+                "Rule: {} {} {:.3} ({:.3} -> {:.3})",
+                self.field,
+                self.condition.label(),
+                self.threshold,
+                before,
+                after
+            )
+        })
+    }
+}
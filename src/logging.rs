@@ -0,0 +1,52 @@
+use bevy::log::{BoxedLayer, LogPlugin};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Destination for the app's own diagnostic output (not the drone's log messages).
+/// Stderr output is always kept; the system log is added on top of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum LogTarget {
+    #[default]
+    Stderr,
+    /// systemd journal, readable with `journalctl -t drone_gui`
+    Journald,
+}
+
+impl LogTarget {
+    pub const ALL: [LogTarget; 2] = [LogTarget::Stderr, LogTarget::Journald];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogTarget::Stderr => "stderr",
+            LogTarget::Journald => "stderr + journald",
+        }
+    }
+}
+
+/// Bevy's log plugin configured for the chosen target
+pub fn log_plugin(target: LogTarget) -> LogPlugin {
+    LogPlugin {
+        custom_layer: match target {
+            LogTarget::Stderr => |_| None,
+            LogTarget::Journald => journald_layer,
+        },
+        ..default()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn journald_layer(_app: &mut App) -> Option<BoxedLayer> {
+    match tracing_journald::layer() {
+        Ok(layer) => Some(Box::new(layer.with_syslog_identifier("drone_gui".to_string()))),
+        Err(e) => {
+            eprintln!("journald unavailable, logging to stderr only: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn journald_layer(_app: &mut App) -> Option<BoxedLayer> {
+    eprintln!("journald logging is only available on Linux");
+    None
+}
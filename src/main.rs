@@ -1,22 +1,79 @@
 mod app;
 mod config;
 mod drone_scene;
+mod export;
+mod flight_log;
+mod headless;
 mod input;
+mod log_buffer;
+mod log_rules;
+mod logging;
 mod parser;
 mod persistence;
 mod protocol;
+mod raw_capture;
+mod spectrum;
+mod status;
 mod telemetry;
+mod transport;
 mod uart;
 mod ui;
 
 
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: drone_gui [--headless --log-file <path>]";
+
+/// Parsed command line: the log file path when running headless, None for the GUI
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<PathBuf>, String> {
+    let mut headless = false;
+    let mut log_file = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => headless = true,
+            "--log-file" => log_file = Some(PathBuf::from(args.next().ok_or("--log-file needs a path")?)),
+            other => return Err(format!("unknown argument '{}'", other)),
+        }
+    }
+    match (headless, log_file) {
+        (false, None) => Ok(None),
+        (true, Some(path)) => Ok(Some(path)),
+        (true, None) => Err("--headless requires --log-file".to_string()),
+        (false, Some(_)) => Err("--log-file is only used with --headless".to_string()),
+    }
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+    let headless_log = match parse_args(args) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let settings = persistence::PersistentSettings::load();
+
+    if let Some(path) = headless_log {
+        if let Err(e) = headless::run(&settings, &path) {
+            eprintln!("Headless logging failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins
+                .set(logging::log_plugin(settings.log_target))
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         title: "Drone Telemetry Monitor".to_string(),
@@ -30,17 +87,59 @@ fn main() {
             EguiPlugin,
         ))
         .add_systems(Startup, drone_scene::setup_drone_scene)
+        .add_systems(Startup, persistence::report_load_system)
+        .add_systems(Startup, app::auto_connect_system)
         .add_systems(Update, drone_scene::update_drone_orientation)
+        .add_systems(Update, drone_scene::update_drone_tint.after(ui::ui_system))
+        .add_systems(Update, drone_scene::update_body_glow.after(ui::ui_system))
+        .add_systems(Update, drone_scene::update_scene_helpers)
         .add_systems(
             Update,
             ui::ui_system.after(drone_scene::update_drone_orientation),
         )
+        .add_systems(Update, app::uart_exit_system)
         .add_systems(Update, app::command_dispatch_system)
-        .add_systems(Update, persistence::auto_save_system)
+        .add_systems(Update, app::config_refresh_system)
+        .add_systems(Update, app::config_push_on_connect_system)
+        .add_systems(Update, app::version_query_on_connect_system)
+        .add_systems(Update, app::ack_timeout_system)
+        .add_systems(Update, app::plot_export_system.after(ui::ui_system))
+        .add_systems(Last, persistence::auto_save_system)
         .add_systems(Last, app::uart_shutdown_system)
-        .insert_resource(app::AppState::default())
+        .insert_resource(app::AppState::from_settings(&settings))
         .insert_resource(app::CommandTimer::default())
         .insert_resource(app::CommandQueue::default())
-        .insert_resource(persistence::PersistentSettings::load())
+        .insert_resource(app::ConfigRefreshTimer::default())
+        .insert_resource(settings)
         .run();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<PathBuf>, String> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parses_gui_and_headless_invocations() {
+        assert_eq!(parse(&[]), Ok(None));
+        assert_eq!(
+            parse(&["--headless", "--log-file", "flight.csv"]),
+            Ok(Some(PathBuf::from("flight.csv")))
+        );
+        assert_eq!(
+            parse(&["--log-file", "flight.csv", "--headless"]),
+            Ok(Some(PathBuf::from("flight.csv")))
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_or_unknown_arguments() {
+        assert!(parse(&["--headless"]).is_err());
+        assert!(parse(&["--headless", "--log-file"]).is_err());
+        assert!(parse(&["--log-file", "flight.csv"]).is_err());
+        assert!(parse(&["--headless", "--log-file", "flight.csv", "--verbose"]).is_err());
+    }
+}
@@ -1,7 +1,34 @@
+use crate::protocol::FirmwareVersion;
+use crate::telemetry::{EscTelemetry, LogLevel};
+
 /// Parse log message from a raw serial line
-/// Format: "LOG:message text here"
-pub fn parse_log(line: &str) -> Option<String> {
-    line.strip_prefix("LOG:").map(str::to_string)
+/// Format: "LOG:message text here" or "LOG:WARN:message text here"
+/// Lines without a recognised level prefix are Info.
+pub fn parse_log(line: &str) -> Option<(LogLevel, String)> {
+    let body = line.strip_prefix("LOG:")?;
+    if let Some((prefix, text)) = body.split_once(':')
+        && let Some(level) = LogLevel::from_prefix(prefix)
+    {
+        return Some((level, text.trim_start().to_string()));
+    }
+    Some((LogLevel::Info, body.to_string()))
+}
+
+/// Calibration report carried in a firmware log message
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CalibrationEvent {
+    Progress(f32),
+    Done,
+}
+
+/// Recognises calibration progress in a log message: "CAL 50%" or "CAL DONE"
+pub fn parse_calibration(message: &str) -> Option<CalibrationEvent> {
+    let rest = message.trim().strip_prefix("CAL")?.trim();
+    if rest.eq_ignore_ascii_case("DONE") {
+        return Some(CalibrationEvent::Done);
+    }
+    let percent: f32 = rest.strip_suffix('%')?.trim().parse().ok()?;
+    Some(CalibrationEvent::Progress(percent.clamp(0.0, 100.0)))
 }
 
 /// Check if the line is an ACK from the flight controller
@@ -10,8 +37,137 @@ pub fn parse_ack(line: &str) -> Option<&str> {
     line.strip_prefix("ACK:")
 }
 
+/// Parse ESC telemetry from a raw serial line
+/// Format: "ESC:t1,t2,t3,t4,c1,c2,c3,c4" with temperatures in °C and currents in A, motors 1-4
+pub fn parse_esc(line: &str) -> Option<EscTelemetry> {
+    let values: Vec<f32> = line
+        .strip_prefix("ESC:")?
+        .split(',')
+        .map(|v| v.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let values: [f32; 8] = values.try_into().ok()?;
+    Some(EscTelemetry {
+        temps: [values[0], values[1], values[2], values[3]],
+        currents: [values[4], values[5], values[6], values[7]],
+        clock_time: chrono::Local::now(),
+    })
+}
+
+/// Parse the firmware's reply to the version query
+/// Format: "VER:<version>", where the version starts with dotted numbers, optionally after a 'v'
+pub fn parse_version(line: &str) -> Option<FirmwareVersion> {
+    let text = line.strip_prefix("VER:")?.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let numeric = text.trim_start_matches(['v', 'V']);
+    let end = numeric
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(numeric.len());
+    let numbers = numeric[..end]
+        .split('.')
+        .map_while(|n| n.parse().ok())
+        .collect();
+    Some(FirmwareVersion {
+        text: text.to_string(),
+        numbers,
+    })
+}
+
 /// Check if the line is an error from the flight controller
 /// Returns the error string
 pub fn parse_err(line: &str) -> Option<&str> {
     line.strip_prefix("ERR:")
 }
+
+/// Parse space- or comma-separated hex bytes typed by the user, e.g. "DE AD BE EF".
+/// Unseparated runs like "DEADBEEF" and an optional "0x" prefix per byte are accepted.
+pub fn parse_hex_payload(input: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for token in input.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()) {
+        let digits = token
+            .strip_prefix("0x")
+            .or_else(|| token.strip_prefix("0X"))
+            .unwrap_or(token);
+        if digits.len() % 2 != 0 {
+            return Err(format!("'{}' has an odd number of hex digits", token));
+        }
+        for pair in digits.as_bytes().chunks(2) {
+            let pair = std::str::from_utf8(pair).map_err(|_| format!("'{}' is not hex", token))?;
+            let byte = u8::from_str_radix(pair, 16).map_err(|_| format!("'{}' is not hex", token))?;
+            bytes.push(byte);
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_payload_accepts_separated_bytes() {
+        assert_eq!(parse_hex_payload("DE AD BE EF"), Ok(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+    }
+
+    #[test]
+    fn hex_payload_accepts_unseparated_run() {
+        assert_eq!(parse_hex_payload("DEADBEEF"), Ok(vec![0xDE, 0xAD, 0xBE, 0xEF]));
+    }
+
+    #[test]
+    fn hex_payload_accepts_prefixed_comma_separated_bytes() {
+        assert_eq!(parse_hex_payload("0xDE,0xAD"), Ok(vec![0xDE, 0xAD]));
+    }
+
+    #[test]
+    fn hex_payload_rejects_odd_digit_count() {
+        assert!(parse_hex_payload("DE A").is_err());
+        assert!(parse_hex_payload("ABC").is_err());
+    }
+
+    #[test]
+    fn hex_payload_rejects_non_hex() {
+        assert!(parse_hex_payload("GG").is_err());
+        assert!(parse_hex_payload("DE ZZ").is_err());
+        assert!(parse_hex_payload("é1").is_err());
+    }
+
+    #[test]
+    fn hex_payload_of_empty_input_is_empty() {
+        assert_eq!(parse_hex_payload(""), Ok(vec![]));
+        assert_eq!(parse_hex_payload("  , "), Ok(vec![]));
+    }
+
+    #[test]
+    fn version_with_dotted_numbers() {
+        let version = parse_version("VER:1.4.2").expect("version parsed");
+        assert_eq!(version.text, "1.4.2");
+        assert_eq!(version.numbers, vec![1, 4, 2]);
+        assert_eq!(version.protocol(), Some(1));
+        assert!(!version.is_incompatible());
+    }
+
+    #[test]
+    fn version_with_prefix_and_suffix() {
+        let version = parse_version("VER:v2.0-dirty").expect("version parsed");
+        assert_eq!(version.text, "v2.0-dirty");
+        assert_eq!(version.numbers, vec![2, 0]);
+        assert_eq!(version.protocol(), Some(2));
+        assert!(version.is_incompatible());
+    }
+
+    #[test]
+    fn version_without_numbers_is_compatible() {
+        let version = parse_version("VER:abc").expect("version parsed");
+        assert_eq!(version.protocol(), None);
+        assert!(!version.is_incompatible());
+    }
+
+    #[test]
+    fn empty_version_is_rejected() {
+        assert!(parse_version("VER:").is_none());
+        assert!(parse_version("VER:  ").is_none());
+        assert!(parse_version("1.4.2").is_none());
+    }
+}
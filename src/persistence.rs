@@ -1,9 +1,18 @@
+use bevy::app::AppExit;
 use bevy::prelude::*;
+use bevy_egui::egui;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::flight_log::FlightLogConfig;
+use crate::log_rules::LogRule;
+use crate::logging::LogTarget;
 use crate::protocol;
+use crate::telemetry::{BufferFullPolicy, DeviationAlarm, OscillationDetector};
+use crate::transport::TransportKind;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PidParameters {
@@ -26,6 +35,206 @@ impl Default for PidParameters {
     }
 }
 
+/// Rule-of-thumb bounds the PID window warns about; values outside them are allowed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PidWarningThresholds {
+    /// Warn when P exceeds this while D is zero
+    pub max_p_without_d: f32,
+    /// Warn when D exceeds this multiple of P
+    pub max_d_to_p: f32,
+    /// Warn when I exceeds this multiple of P
+    pub max_i_to_p: f32,
+}
+
+impl Default for PidWarningThresholds {
+    fn default() -> Self {
+        Self {
+            max_p_without_d: 2.0,
+            max_d_to_p: 1.0,
+            max_i_to_p: 1.0,
+        }
+    }
+}
+
+/// Problems with a set of PID gains: `errors` block sending, `warnings` only advise
+#[derive(Debug, Default)]
+pub struct PidCheck {
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl PidParameters {
+    pub fn to_controller(&self) -> protocol::PIDController {
+        protocol::PIDController {
+            p: self.p,
+            i: self.i,
+            d: self.d,
+            i_limit: self.i_limit,
+            pid_limit: self.pid_limit,
+        }
+    }
+
+    pub fn check(&self, thresholds: &PidWarningThresholds) -> PidCheck {
+        let mut check = PidCheck::default();
+        if self.pid_limit <= 0.0 {
+            check.errors.push("PID limit is 0, so the controller output is always clamped to 0".to_string());
+        }
+        if self.i_limit < self.i {
+            check.errors.push(format!("I limit {:.3} is below I {:.3}", self.i_limit, self.i));
+        }
+        if self.d == 0.0 && self.p > thresholds.max_p_without_d {
+            check.warnings.push(format!(
+                "P above {:.2} with no D tends to oscillate",
+                thresholds.max_p_without_d
+            ));
+        }
+        let p = self.p.abs();
+        if self.d.abs() > p * thresholds.max_d_to_p {
+            check.warnings.push(format!(
+                "D is more than {:.1}× P, which amplifies gyro noise",
+                thresholds.max_d_to_p
+            ));
+        }
+        if self.i.abs() > p * thresholds.max_i_to_p {
+            check.warnings.push(format!(
+                "I is more than {:.1}× P, which winds up and overshoots",
+                thresholds.max_i_to_p
+            ));
+        }
+        check
+    }
+}
+
+/// One of the side-by-side sections above the plots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MainSection {
+    Viewport,
+    Commands,
+    Logs,
+}
+
+impl MainSection {
+    pub const ALL: [MainSection; 3] = [MainSection::Viewport, MainSection::Commands, MainSection::Logs];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MainSection::Viewport => "3D view",
+            MainSection::Commands => "Commands",
+            MainSection::Logs => "Logs",
+        }
+    }
+
+    /// Position of this section's width share in `PersistentSettings::section_shares`
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Which settings "Reset to Defaults" puts back
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingsReset {
+    PidGains,
+    FlightConfig,
+    Everything,
+}
+
+impl SettingsReset {
+    pub const ALL: [SettingsReset; 3] = [SettingsReset::PidGains, SettingsReset::FlightConfig, SettingsReset::Everything];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsReset::PidGains => "PID gains (all axes)",
+            SettingsReset::FlightConfig => "Flight config",
+            SettingsReset::Everything => "Everything",
+        }
+    }
+}
+
+/// UI color scheme
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// Follow the operating system setting
+    System,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::System];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::System => "System",
+        }
+    }
+
+    pub fn preference(self) -> egui::ThemePreference {
+        match self {
+            Theme::Dark => egui::ThemePreference::Dark,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::System => egui::ThemePreference::System,
+        }
+    }
+}
+
+/// Colors for roll/pitch/yaw, used by the plots (gyro X/Y/Z included), the attitude
+/// readouts and the 3D model's front marker, plus the width of those plot lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlotStyle {
+    pub roll: [u8; 3],
+    pub pitch: [u8; 3],
+    pub yaw: [u8; 3],
+    pub front_marker: [u8; 3],
+    pub line_width: f32,
+}
+
+impl Default for PlotStyle {
+    fn default() -> Self {
+        Self {
+            roll: [230, 60, 60],
+            pitch: [40, 170, 70],
+            yaw: [60, 120, 240],
+            front_marker: [0, 204, 0],
+            line_width: 1.5,
+        }
+    }
+}
+
+impl PlotStyle {
+    /// Okabe-Ito colors, distinguishable with the common forms of color blindness
+    pub fn colorblind() -> Self {
+        Self {
+            roll: [213, 94, 0],
+            pitch: [0, 158, 115],
+            yaw: [0, 114, 178],
+            front_marker: [0, 158, 115],
+            line_width: 2.0,
+        }
+    }
+
+    /// Roll, pitch and yaw colors
+    pub fn axis_colors(&self) -> [egui::Color32; 3] {
+        [self.roll, self.pitch, self.yaw].map(|[r, g, b]| egui::Color32::from_rgb(r, g, b))
+    }
+
+    pub fn front_marker_color(&self) -> Color {
+        let [r, g, b] = self.front_marker;
+        Color::srgb_u8(r, g, b)
+    }
+}
+
+/// Named serial setup (e.g. bench vs field radio)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionProfile {
+    pub name: String,
+    pub port_path: String,
+    pub baud_rate: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Resource)]
 pub struct PersistentSettings {
     // PID parameters for each axis
@@ -54,11 +263,199 @@ pub struct PersistentSettings {
     #[serde(default = "default_max_yaw_rate")]
     pub max_yaw_rate: f32,
 
+    // Periodic resend of the flight config while connected
+    #[serde(default)]
+    pub config_refresh_enabled: bool,
+    #[serde(default = "default_config_refresh_secs")]
+    pub config_refresh_secs: f32,
+    // Send the flight config once every time the port opens
+    #[serde(default)]
+    pub push_config_on_connect: bool,
+
+    // Link last used in the connection panel, restored at startup.
+    // No port on first run, so the first detected port is picked.
+    #[serde(default)]
+    pub port_path: Option<String>,
+    #[serde(default = "default_baud_rate")]
+    pub baud_rate: u32,
+    #[serde(default)]
+    pub transport: TransportKind,
+    #[serde(default)]
+    pub auto_connect_on_launch: bool,
+    #[serde(default = "default_tcp_address")]
+    pub tcp_address: String,
+
+    // Reopen the serial port as soon as its settings change while connected
+    #[serde(default)]
+    pub reconnect_on_config_change: bool,
+
+    // Behaviour of the telemetry buffer once it reaches MAX_POINTS
+    #[serde(default)]
+    pub buffer_full_policy: BufferFullPolicy,
+    #[serde(default = "default_true")]
+    pub rebase_on_timestamp_rewind: bool,
+    #[serde(default)]
+    pub detect_timestamp_gaps: bool,
+
+    // Log lines containing any of these substrings are dropped
+    #[serde(default)]
+    pub log_mute_patterns: Vec<String>,
+
+    // Log panel scrollback, in messages
+    #[serde(default = "default_log_scrollback")]
+    pub log_scrollback: usize,
+
+    // Telemetry conditions that produce log entries
+    #[serde(default)]
+    pub log_rules: Vec<LogRule>,
+
+    // Rolling on-disk copy of the log panel
+    #[serde(default)]
+    pub flight_log_enabled: bool,
+    #[serde(default)]
+    pub flight_log_include_frame_errors: bool,
+    #[serde(default = "default_flight_log_max_kb")]
+    pub flight_log_max_kb: u64,
+    #[serde(default = "default_flight_log_keep")]
+    pub flight_log_keep: usize,
+
+    // Arrow-key increment in the PID tuning window
+    #[serde(default = "default_pid_nudge_step")]
+    pub pid_nudge_step: f32,
+    #[serde(default)]
+    pub pid_warning_thresholds: PidWarningThresholds,
+
+    // 3D view helpers
+    #[serde(default = "default_true")]
+    pub show_grid: bool,
+    #[serde(default)]
+    pub show_axes: bool,
+
+    // Roll/pitch/yaw colors and line width shared by plots, readouts and the 3D model
+    #[serde(default)]
+    pub plot_style: PlotStyle,
+
+    // Width of PNG plot exports, independent of the on-screen size
+    #[serde(default = "default_plot_export_width")]
+    pub plot_export_width: u32,
+
+    // Warn in the log when gyro vibration exceeds the threshold (deg/s RMS)
+    #[serde(default)]
+    pub vibration_alarm_enabled: bool,
+    #[serde(default = "default_vibration_threshold")]
+    pub vibration_threshold: f32,
+
+    // Warn when roll/pitch stays off its setpoint by more than the margin for the hold time
+    #[serde(default)]
+    pub deviation_alarm_enabled: bool,
+    #[serde(default = "default_deviation_margin_deg")]
+    pub deviation_margin_deg: f32,
+    #[serde(default = "default_deviation_hold_secs")]
+    pub deviation_hold_secs: f32,
+
+    // Mark and log roll/pitch error that swings past the amplitude, back and forth, within the window
+    #[serde(default)]
+    pub oscillation_detect_enabled: bool,
+    #[serde(default = "default_oscillation_amplitude_deg")]
+    pub oscillation_amplitude_deg: f32,
+    #[serde(default = "default_oscillation_window_secs")]
+    pub oscillation_window_secs: f32,
+
+    // Seconds to wait for a command ACK before warning
+    #[serde(default = "default_ack_timeout_secs")]
+    pub ack_timeout_secs: f32,
+    /// Give up waiting for "CAL DONE" after this long
+    #[serde(default = "default_calibration_timeout_secs")]
+    pub calibration_timeout_secs: f32,
+
+    #[serde(default)]
+    pub theme: Theme,
+
+    // Width shares of the viewport and commands sections; logs take the rest
+    #[serde(default = "default_section_fractions")]
+    pub section_fractions: [f32; 2],
+    // Left-to-right order of the main sections, which are hidden, and whether the plots are
+    #[serde(default = "default_section_order")]
+    pub section_order: [MainSection; 3],
+    #[serde(default)]
+    pub hidden_sections: Vec<MainSection>,
+    #[serde(default)]
+    pub plots_collapsed: bool,
+    // Larger controls and text for the Pi touchscreen
+    #[serde(default)]
+    pub touch_mode: bool,
+
+    // Saved port/baud combinations for the quick-switcher
+    #[serde(default)]
+    pub connection_profiles: Vec<ConnectionProfile>,
+
+    // Where the app's own diagnostics go (applied at startup)
+    #[serde(default)]
+    pub log_target: LogTarget,
+
+    // Outcome of `load`, reported once logging is running (not persisted)
+    #[serde(skip)]
+    load_report: Option<Result<String, String>>,
+
     // Currently selected axis for tuning (not persisted, just for UI state)
     #[serde(skip)]
     pub selected_tune_axis: protocol::SelectPID,
 }
 
+fn default_plot_export_width() -> u32 {
+    1920
+}
+fn default_pid_nudge_step() -> f32 {
+    0.01
+}
+fn default_vibration_threshold() -> f32 {
+    30.0
+}
+fn default_deviation_margin_deg() -> f32 {
+    10.0
+}
+fn default_deviation_hold_secs() -> f32 {
+    0.5
+}
+fn default_oscillation_amplitude_deg() -> f32 {
+    3.0
+}
+fn default_oscillation_window_secs() -> f32 {
+    1.0
+}
+fn default_section_fractions() -> [f32; 2] {
+    [0.25, 0.20]
+}
+fn default_section_order() -> [MainSection; 3] {
+    MainSection::ALL
+}
+fn default_ack_timeout_secs() -> f32 {
+    2.0
+}
+fn default_true() -> bool {
+    true
+}
+fn default_baud_rate() -> u32 {
+    crate::config::BAUD_RATE
+}
+fn default_calibration_timeout_secs() -> f32 {
+    15.0
+}
+fn default_tcp_address() -> String {
+    "127.0.0.1:5760".to_string()
+}
+fn default_config_refresh_secs() -> f32 {
+    5.0
+}
+fn default_log_scrollback() -> usize {
+    crate::config::MAX_LOG_MESSAGES
+}
+fn default_flight_log_max_kb() -> u64 {
+    1024
+}
+fn default_flight_log_keep() -> usize {
+    5
+}
 fn default_throttle_hover() -> f32 {
     0.45
 }
@@ -89,15 +486,64 @@ impl Default for PersistentSettings {
             max_roll_angle: default_max_roll_angle(),
             max_pitch_angle: default_max_pitch_angle(),
             max_yaw_rate: default_max_yaw_rate(),
+            config_refresh_enabled: false,
+            config_refresh_secs: default_config_refresh_secs(),
+            push_config_on_connect: false,
+            port_path: None,
+            baud_rate: default_baud_rate(),
+            transport: TransportKind::default(),
+            auto_connect_on_launch: false,
+            tcp_address: default_tcp_address(),
+            reconnect_on_config_change: false,
+            buffer_full_policy: BufferFullPolicy::default(),
+            rebase_on_timestamp_rewind: true,
+            detect_timestamp_gaps: false,
+            log_mute_patterns: Vec::new(),
+            log_scrollback: default_log_scrollback(),
+            log_rules: Vec::new(),
+            flight_log_enabled: false,
+            flight_log_include_frame_errors: false,
+            flight_log_max_kb: default_flight_log_max_kb(),
+            flight_log_keep: default_flight_log_keep(),
+            pid_nudge_step: default_pid_nudge_step(),
+            pid_warning_thresholds: PidWarningThresholds::default(),
+            show_grid: true,
+            plot_style: PlotStyle::default(),
+            show_axes: false,
+            plot_export_width: default_plot_export_width(),
+            vibration_alarm_enabled: false,
+            vibration_threshold: default_vibration_threshold(),
+            deviation_alarm_enabled: false,
+            deviation_margin_deg: default_deviation_margin_deg(),
+            deviation_hold_secs: default_deviation_hold_secs(),
+            oscillation_detect_enabled: false,
+            oscillation_amplitude_deg: default_oscillation_amplitude_deg(),
+            oscillation_window_secs: default_oscillation_window_secs(),
+            ack_timeout_secs: default_ack_timeout_secs(),
+            calibration_timeout_secs: default_calibration_timeout_secs(),
+            theme: Theme::default(),
+            section_fractions: default_section_fractions(),
+            section_order: default_section_order(),
+            hidden_sections: Vec::new(),
+            plots_collapsed: false,
+            touch_mode: false,
+            connection_profiles: Vec::new(),
+            log_target: LogTarget::default(),
+            load_report: None,
             selected_tune_axis: protocol::SelectPID::Roll,
         }
     }
 }
 
+/// Per-user application directory (e.g. ~/.config/drone_gui)
+pub fn app_config_dir() -> PathBuf {
+    let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    config_dir.join("drone_gui")
+}
+
 impl PersistentSettings {
     fn settings_path() -> PathBuf {
-        let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-        let app_config_dir = config_dir.join("drone_gui");
+        let app_config_dir = app_config_dir();
         let _ = fs::create_dir_all(&app_config_dir);
         app_config_dir.join("settings.json")
     }
@@ -105,33 +551,50 @@ impl PersistentSettings {
     pub fn load() -> Self {
         let path = Self::settings_path();
 
-        match fs::read_to_string(&path) {
-            Ok(contents) => match serde_json::from_str(&contents) {
-                Ok(settings) => {
-                    println!("Loaded settings from {:?}", path);
-                    settings
-                }
-                Err(e) => {
-                    eprintln!("Failed to parse settings file: {}", e);
-                    Self::default()
-                }
+        // Settings load before the log subscriber exists, so the outcome is kept
+        // and reported by `report_load_system`
+        let (mut settings, report) = match fs::read_to_string(&path) {
+            Ok(contents) => match serde_json::from_str::<Self>(&contents) {
+                Ok(settings) => (settings, Ok(format!("Loaded settings from {:?}", path))),
+                Err(e) => (
+                    Self::default(),
+                    Err(format!("Failed to parse settings file: {}", e)),
+                ),
             },
-            Err(_) => {
-                println!("No settings file found, using defaults");
-                Self::default()
-            }
-        }
+            Err(_) => (
+                Self::default(),
+                Ok("No settings file found, using defaults".to_string()),
+            ),
+        };
+        settings.load_report = Some(report);
+        settings
     }
 
-    pub fn save(&self) -> Result<(), String> {
-        let path = Self::settings_path();
+    fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize settings: {}", e))
+    }
 
-        match serde_json::to_string_pretty(self) {
-            Ok(json) => match fs::write(&path, json) {
-                Ok(()) => Ok(()),
-                Err(e) => Err(format!("Failed to write settings file: {}", e)),
-            },
-            Err(e) => Err(format!("Failed to serialize settings: {}", e)),
+    fn write_json(json: &str) -> Result<(), String> {
+        fs::write(Self::settings_path(), json).map_err(|e| format!("Failed to write settings file: {}", e))
+    }
+
+    /// Puts the settings covered by `scope` back to their defaults
+    pub fn reset(&mut self, scope: SettingsReset) {
+        let defaults = Self::default();
+        match scope {
+            SettingsReset::PidGains => {
+                for axis in protocol::SelectPID::ALL {
+                    *self.get_pid_mut(axis) = defaults.get_pid(axis).clone();
+                }
+            }
+            SettingsReset::FlightConfig => {
+                self.throttle_hover = defaults.throttle_hover;
+                self.throttle_expo = defaults.throttle_expo;
+                self.max_roll_angle = defaults.max_roll_angle;
+                self.max_pitch_angle = defaults.max_pitch_angle;
+                self.max_yaw_rate = defaults.max_yaw_rate;
+            }
+            SettingsReset::Everything => *self = defaults,
         }
     }
 
@@ -157,6 +620,51 @@ impl PersistentSettings {
         }
     }
 
+    pub fn flight_log_config(&self) -> Option<FlightLogConfig> {
+        self.flight_log_enabled.then(|| FlightLogConfig {
+            dir: app_config_dir().join("logs"),
+            max_bytes: self.flight_log_max_kb * 1024,
+            keep: self.flight_log_keep,
+            include_frame_errors: self.flight_log_include_frame_errors,
+        })
+    }
+
+    pub fn deviation_alarm(&self) -> Option<DeviationAlarm> {
+        self.deviation_alarm_enabled.then(|| DeviationAlarm {
+            margin: self.deviation_margin_deg.to_radians(),
+            hold_ms: (self.deviation_hold_secs * 1000.0) as u32,
+        })
+    }
+
+    /// Shown sections, left to right; an order repeating a section shows it once
+    pub fn visible_sections(&self) -> Vec<MainSection> {
+        let mut sections = Vec::new();
+        for section in self.section_order {
+            if !sections.contains(&section) && !self.hidden_sections.contains(&section) {
+                sections.push(section);
+            }
+        }
+        sections
+    }
+
+    /// Width shares of every section, indexed by `MainSection::index`; logs take the rest
+    pub fn section_shares(&self) -> [f32; 3] {
+        let [viewport, commands] = self.section_fractions;
+        [viewport, commands, 1.0 - viewport - commands]
+    }
+
+    /// How long a sent command may go without an ACK
+    pub fn ack_timeout(&self) -> Duration {
+        Duration::from_secs_f32(self.ack_timeout_secs.max(0.1))
+    }
+
+    pub fn oscillation_detector(&self) -> Option<OscillationDetector> {
+        self.oscillation_detect_enabled.then(|| OscillationDetector {
+            amplitude: self.oscillation_amplitude_deg.to_radians(),
+            window_ms: (self.oscillation_window_secs * 1000.0) as u32,
+        })
+    }
+
     pub fn to_config_packet(&self) -> protocol::ConfigPacket {
         protocol::ConfigPacket {
             throttle_hover: self.throttle_hover,
@@ -168,11 +676,116 @@ impl PersistentSettings {
     }
 }
 
-pub fn auto_save_system(settings: Res<PersistentSettings>) {
-    if settings.is_changed()
-        && !settings.is_added()
-        && let Err(e) = settings.save()
-    {
-        eprintln!("Failed to auto-save settings: {}", e);
+/// Settings are written once edits have paused this long, e.g. after a PID drag ends
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Settings are serialized to look for edits at most this often
+const EDIT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Hashes of the settings JSON as last written and as last seen, and when it last changed
+#[derive(Default)]
+pub struct AutoSaveState {
+    saved: Option<u64>,
+    seen: Option<u64>,
+    last_edit: Duration,
+    last_check: Option<Duration>,
+}
+
+impl AutoSaveState {
+    /// Records the settings JSON hash seen at `now`. True once it differs from the saved
+    /// file and has stayed the same for `SAVE_DEBOUNCE`.
+    fn should_save(&mut self, hash: u64, now: Duration) -> bool {
+        let Some(saved) = self.saved else {
+            // First run: the file already matches what was just loaded
+            self.saved = Some(hash);
+            self.seen = Some(hash);
+            return false;
+        };
+        if self.seen != Some(hash) {
+            self.seen = Some(hash);
+            self.last_edit = now;
+        }
+        saved != hash && now - self.last_edit >= SAVE_DEBOUNCE
+    }
+}
+
+fn json_hash(json: &str) -> u64 {
+    let mut hasher = std::hash::DefaultHasher::new();
+    json.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Saves settings whenever their contents differ from the file, debounced so a slider
+/// drag is one write, and flushed immediately on exit so no edit is lost. This is the
+/// single save path; UI code only edits `PersistentSettings`.
+pub fn auto_save_system(
+    time: Res<Time>,
+    settings: Res<PersistentSettings>,
+    mut exit_events: EventReader<AppExit>,
+    mut state: Local<AutoSaveState>,
+) {
+    let exiting = exit_events.read().next().is_some();
+    let now = time.elapsed();
+    // The UI borrows settings mutably every frame, so change detection alone can't tell
+    // real edits; the JSON is compared instead, but not on every frame
+    if !exiting && state.last_check.is_some_and(|last| now - last < EDIT_CHECK_INTERVAL) {
+        return;
+    }
+    state.last_check = Some(now);
+
+    let json = match settings.to_json() {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Failed to auto-save settings: {}", e);
+            return;
+        }
+    };
+    let hash = json_hash(&json);
+    let due = state.should_save(hash, now);
+    if due || (exiting && state.saved != Some(hash)) {
+        match PersistentSettings::write_json(&json) {
+            Ok(()) => state.saved = Some(hash),
+            Err(e) => error!("Failed to auto-save settings: {}", e),
+        }
+    }
+}
+
+pub fn report_load_system(settings: Res<PersistentSettings>) {
+    match &settings.load_report {
+        Some(Ok(message)) => info!("{}", message),
+        Some(Err(message)) => warn!("{}", message),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_save_waits_for_edits_to_pause() {
+        let mut state = AutoSaveState::default();
+        let ms = Duration::from_millis;
+        assert!(!state.should_save(0, ms(0)));
+
+        // A two-second slider drag changes the settings on every check
+        for step in 1..=20u64 {
+            assert!(!state.should_save(step, ms(step * 100)), "saved mid-drag at step {}", step);
+        }
+        assert!(!state.should_save(20, ms(2400)));
+        assert!(state.should_save(20, ms(2500)));
+
+        state.saved = Some(20);
+        assert!(!state.should_save(20, ms(3500)));
+    }
+
+    #[test]
+    fn reverted_edit_is_not_saved() {
+        let mut state = AutoSaveState::default();
+        let ms = Duration::from_millis;
+        state.should_save(1, ms(0));
+        assert!(!state.should_save(2, ms(100)));
+        assert!(!state.should_save(1, ms(200)));
+        assert!(!state.should_save(1, ms(1000)));
     }
 }
@@ -1,6 +1,9 @@
 use bytemuck::{Pod, Zeroable};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
-use crate::app::CommandQueue;
+use crate::app::{COMMAND_INTERVAL, CommandQueue};
+use crate::parser::CalibrationEvent;
 
 // Binary protocol type bytes - matches bluetooth.h BT_CMD_* constants
 const BT_CMD_CALIBRATE: u8 = 0x01;
@@ -8,6 +11,11 @@ const BT_CMD_SET_PID: u8 = 0x02;
 const BT_CMD_CONFIG: u8 = 0x04;
 const BT_CMD_SAVE: u8 = 0x05;
 
+/// Wire protocol this GUI speaks: the major number of the firmware version
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Text query the firmware answers with a "VER:" line
+pub const VERSION_QUERY: &[u8] = b"FC:VERSION\n";
+
 /// CRC8-DVB-S2 - matches firmware implementation
 fn crc8_dvb_s2(data: &[u8]) -> u8 {
     let mut crc: u8 = 0;
@@ -35,6 +43,23 @@ pub struct PIDTunePacket {
     pub axis: u8,
 }
 
+impl PIDTunePacket {
+    pub fn new(axis: SelectPID, pid: &PIDController) -> Self {
+        Self {
+            p: pid.p,
+            i: pid.i,
+            d: pid.d,
+            i_limit: pid.i_limit,
+            pid_limit: pid.pid_limit,
+            axis: axis as u8,
+        }
+    }
+}
+
+// Every payload must fit the frame's one-byte LEN
+const _: () = assert!(size_of::<PIDTunePacket>() <= u8::MAX as usize);
+const _: () = assert!(size_of::<ConfigPacket>() <= u8::MAX as usize);
+
 /// Flight configuration: throttle curve and angle sensitivity.
 /// Matches firmware CommandConfig struct (20 bytes).
 #[repr(C, packed)]
@@ -47,6 +72,35 @@ pub struct ConfigPacket {
     pub max_yaw_rate: f32,
 }
 
+impl ConfigPacket {
+    /// Field names and raw values in wire order
+    pub fn fields(&self) -> [(&'static str, f32); 5] {
+        let c = *self;
+        [
+            ("throttle_hover", c.throttle_hover),
+            ("throttle_expo", c.throttle_expo),
+            ("max_roll_angle", c.max_roll_angle),
+            ("max_pitch_angle", c.max_pitch_angle),
+            ("max_yaw_rate", c.max_yaw_rate),
+        ]
+    }
+
+    /// One-line summary for logs, angles shown in degrees
+    pub fn summary(&self) -> String {
+        let c = *self;
+        let (hover, expo) = (c.throttle_hover, c.throttle_expo);
+        let (roll, pitch, yaw) = (c.max_roll_angle, c.max_pitch_angle, c.max_yaw_rate);
+        format!(
+            "hover={:.2} expo={:.2} roll={:.0}° pitch={:.0}° yaw={:.0}°/s",
+            hover,
+            expo,
+            roll.to_degrees(),
+            pitch.to_degrees(),
+            yaw.to_degrees()
+        )
+    }
+}
+
 pub struct PIDController {
     pub p: f32,
     pub i: f32,
@@ -67,6 +121,28 @@ pub enum SelectPID {
     VelocityZ = 0x5,
 }
 
+impl SelectPID {
+    pub const ALL: [SelectPID; 6] = [
+        SelectPID::Roll,
+        SelectPID::Pitch,
+        SelectPID::Yaw,
+        SelectPID::VelocityX,
+        SelectPID::VelocityY,
+        SelectPID::VelocityZ,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SelectPID::Roll => "Roll",
+            SelectPID::Pitch => "Pitch",
+            SelectPID::Yaw => "Yaw",
+            SelectPID::VelocityX => "Velocity X",
+            SelectPID::VelocityY => "Velocity Y",
+            SelectPID::VelocityZ => "Velocity Z",
+        }
+    }
+}
+
 /// Commands supported over Bluetooth serial - matches BT_CMD_* in bluetooth.h
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CommandType {
@@ -77,6 +153,33 @@ pub enum CommandType {
 }
 
 impl CommandType {
+    /// Type string the firmware replies with in `ACK:<type>`
+    pub fn ack_name(&self) -> &'static str {
+        match self {
+            CommandType::Calibrate => "CALIBRATE",
+            CommandType::TunePID(_) => "PID",
+            CommandType::Config(_) => "CONFIG",
+            CommandType::Save => "SAVE",
+        }
+    }
+
+    /// Short human-readable summary for the command echo log
+    pub fn describe(&self) -> String {
+        match self {
+            CommandType::Calibrate => "CALIBRATE".to_string(),
+            CommandType::TunePID(p) => {
+                let packet = *p;
+                let (kp, ki, kd) = (packet.p, packet.i, packet.d);
+                format!(
+                    "SET_PID axis {} P={:.3} I={:.3} D={:.3}",
+                    packet.axis, kp, ki, kd
+                )
+            }
+            CommandType::Config(_) => "CONFIG".to_string(),
+            CommandType::Save => "SAVE".to_string(),
+        }
+    }
+
     /// Encode command as a binary frame: 0xA5 | TYPE | LEN | PAYLOAD | CRC8
     pub fn to_binary_frame(&self) -> Vec<u8> {
         let (type_byte, payload): (u8, &[u8]) = match self {
@@ -100,6 +203,209 @@ impl CommandType {
     }
 }
 
+/// A sent command still waiting for its ACK
+#[derive(Debug, Clone)]
+pub struct OutstandingCommand {
+    /// Local sequence number (the wire protocol carries none)
+    pub seq: u32,
+    pub command: CommandType,
+    pub sent_at: Instant,
+    /// Set once the ACK timeout has been reported
+    pub timed_out: bool,
+    /// Id of the "Send All" batch the command was queued in
+    pub batch: Option<u32>,
+}
+
+/// A command waiting in the `CommandQueue`
+#[derive(Debug, Clone, Copy)]
+pub struct QueuedCommand {
+    pub command: CommandType,
+    /// Id of the "Send All" batch it belongs to; batch members are never coalesced away
+    pub batch: Option<u32>,
+}
+
+/// Config and PID commands queued together by "Send All", applied once all are ACKed
+#[derive(Clone, Copy, Debug)]
+pub struct ConfigBatch {
+    pub id: u32,
+    pub started: Instant,
+    /// The batch fails if it isn't fully ACKed this long after it was queued
+    pub deadline: Duration,
+    pub expected: usize,
+    pub acked: usize,
+    /// One of the batch's commands hit the ACK timeout
+    pub failed: bool,
+}
+
+/// Firmware version from a "VER:" reply, e.g. "VER:1.4.2" or "VER:v1.4.2-dirty"
+#[derive(Clone, Debug, PartialEq)]
+pub struct FirmwareVersion {
+    pub text: String,
+    /// Dotted numbers at the start of the version, e.g. [1, 4, 2]
+    pub numbers: Vec<u32>,
+}
+
+impl FirmwareVersion {
+    /// Wire protocol version the firmware speaks, if its version starts with a number
+    pub fn protocol(&self) -> Option<u32> {
+        self.numbers.first().copied()
+    }
+
+    /// True when the firmware's protocol is known and isn't the one this GUI encodes
+    pub fn is_incompatible(&self) -> bool {
+        self.protocol().is_some_and(|p| p != PROTOCOL_VERSION)
+    }
+}
+
+/// A calibration the firmware is running, from sending Calibrate until "CAL DONE"
+#[derive(Clone, Copy, Debug)]
+pub struct CalibrationProgress {
+    pub started: Instant,
+    /// Last "CAL n%" reported, if any
+    pub percent: Option<f32>,
+}
+
+/// Matches firmware ACKs to sent commands. ACKs only name the command type,
+/// so each one acknowledges the oldest outstanding command of that type.
+#[derive(Debug, Default)]
+pub struct CommandTracker {
+    next_seq: u32,
+    next_batch_id: u32,
+    pub outstanding: VecDeque<OutstandingCommand>,
+    /// Most recent config sent on this connection
+    pub last_config: Option<ConfigPacket>,
+    /// Set while a calibration is running; flight commands are disabled meanwhile
+    pub calibration: Option<CalibrationProgress>,
+    /// "Send All" awaiting its ACKs
+    pub batch: Option<ConfigBatch>,
+}
+
+/// Oldest entries are dropped beyond this many unacknowledged commands
+const MAX_OUTSTANDING: usize = 32;
+
+impl CommandTracker {
+    /// Records a sent command and returns its sequence number
+    pub fn record_sent(&mut self, command: CommandType, batch: Option<u32>) -> u32 {
+        match command {
+            CommandType::Config(config) => self.last_config = Some(config),
+            CommandType::Calibrate => {
+                self.calibration = Some(CalibrationProgress {
+                    started: Instant::now(),
+                    percent: None,
+                })
+            }
+            _ => {}
+        }
+        self.next_seq = self.next_seq.wrapping_add(1);
+        if self.outstanding.len() >= MAX_OUTSTANDING {
+            self.outstanding.pop_front();
+        }
+        self.outstanding.push_back(OutstandingCommand {
+            seq: self.next_seq,
+            command,
+            sent_at: Instant::now(),
+            timed_out: false,
+            batch,
+        });
+        self.next_seq
+    }
+
+    /// Removes and returns the oldest outstanding command the ACK type refers to
+    pub fn acknowledge(&mut self, ack: &str) -> Option<OutstandingCommand> {
+        let ack = ack.trim();
+        let index = self
+            .outstanding
+            .iter()
+            .position(|c| c.command.ack_name().eq_ignore_ascii_case(ack))?;
+        let command = self.outstanding.remove(index)?;
+        if let Some(batch) = &mut self.batch
+            && command.batch == Some(batch.id)
+        {
+            batch.acked += 1;
+        }
+        Some(command)
+    }
+
+    /// Starts tracking a "Send All" of `expected` commands and returns the id to queue them with
+    pub fn begin_batch(&mut self, expected: usize, deadline: Duration) -> u32 {
+        self.next_batch_id = self.next_batch_id.wrapping_add(1);
+        self.batch = Some(ConfigBatch {
+            id: self.next_batch_id,
+            started: Instant::now(),
+            deadline,
+            expected,
+            acked: 0,
+            failed: false,
+        });
+        self.next_batch_id
+    }
+
+    /// Ends the batch once every command was ACKed, or one timed out or the deadline passed
+    pub fn take_finished_batch(&mut self) -> Option<ConfigBatch> {
+        self.batch.take_if(|b| b.failed || b.acked >= b.expected)
+    }
+
+    /// Marks commands older than `timeout` as timed out and returns the newly expired ones.
+    /// Also fails the batch when one of its commands expired or its deadline passed.
+    pub fn expire(&mut self, timeout: Duration) -> Vec<OutstandingCommand> {
+        let mut expired = Vec::new();
+        for command in self.outstanding.iter_mut() {
+            if !command.timed_out && command.sent_at.elapsed() >= timeout {
+                command.timed_out = true;
+                if let Some(batch) = &mut self.batch
+                    && command.batch == Some(batch.id)
+                {
+                    batch.failed = true;
+                }
+                expired.push(command.clone());
+            }
+        }
+        if let Some(batch) = &mut self.batch
+            && batch.started.elapsed() >= batch.deadline
+        {
+            batch.failed = true;
+        }
+        expired
+    }
+
+    pub fn remove(&mut self, seq: u32) -> Option<OutstandingCommand> {
+        let index = self.outstanding.iter().position(|c| c.seq == seq)?;
+        self.outstanding.remove(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.outstanding.clear();
+        self.last_config = None;
+        self.calibration = None;
+        self.batch = None;
+    }
+
+    pub fn is_calibrating(&self) -> bool {
+        self.calibration.is_some()
+    }
+
+    /// Applies a calibration report; returns the elapsed time when it finished the calibration
+    pub fn update_calibration(&mut self, event: CalibrationEvent) -> Option<Duration> {
+        let calibration = self.calibration.as_mut()?;
+        match event {
+            CalibrationEvent::Progress(percent) => {
+                calibration.percent = Some(percent);
+                None
+            }
+            CalibrationEvent::Done => self.calibration.take().map(|c| c.started.elapsed()),
+        }
+    }
+
+    /// Ends a calibration that has run longer than `timeout`; returns true if one was ended
+    pub fn expire_calibration(&mut self, timeout: Duration) -> bool {
+        let expired = self.calibration.is_some_and(|c| c.started.elapsed() >= timeout);
+        if expired {
+            self.calibration = None;
+        }
+        expired
+    }
+}
+
 pub fn send_command_calibrate(queue: &CommandQueue) -> Result<(), String> {
     queue.enqueue(CommandType::Calibrate);
     Ok(())
@@ -110,14 +416,16 @@ pub fn send_command_tune_pid(
     axis: SelectPID,
     pid: PIDController,
 ) -> Result<(), String> {
-    queue.enqueue(CommandType::TunePID(PIDTunePacket {
-        p: pid.p,
-        i: pid.i,
-        d: pid.d,
-        i_limit: pid.i_limit,
-        pid_limit: pid.pid_limit,
-        axis: axis as u8,
-    }));
+    queue.enqueue(CommandType::TunePID(PIDTunePacket::new(axis, &pid)));
+    Ok(())
+}
+
+/// Queues a tune command per axis; unlike `send_command_tune_pid` these don't
+/// replace each other while waiting to be sent
+pub fn send_command_tune_pids(queue: &CommandQueue, pids: &[(SelectPID, PIDController)]) -> Result<(), String> {
+    for (axis, pid) in pids {
+        queue.enqueue_uncoalesced(CommandType::TunePID(PIDTunePacket::new(*axis, pid)));
+    }
     Ok(())
 }
 
@@ -126,7 +434,77 @@ pub fn send_command_config(queue: &CommandQueue, config: ConfigPacket) -> Result
     Ok(())
 }
 
+/// Queues the flight config followed by the gains of every PID axis as one batch and
+/// returns the number of commands queued. The batch fails unless all are ACKed within
+/// the time it takes to send them plus `ack_timeout`.
+pub fn send_all_config(
+    queue: &CommandQueue,
+    tracker: &mut CommandTracker,
+    config: ConfigPacket,
+    pids: [(SelectPID, PIDController); 6],
+    ack_timeout: Duration,
+) -> usize {
+    let count = 1 + pids.len();
+    let deadline = COMMAND_INTERVAL * count as u32 + ack_timeout;
+    let batch = tracker.begin_batch(count, deadline);
+    queue.enqueue_batch(CommandType::Config(config), batch);
+    for (axis, pid) in pids {
+        queue.enqueue_batch(CommandType::TunePID(PIDTunePacket::new(axis, &pid)), batch);
+    }
+    count
+}
+
 pub fn send_command_save(queue: &CommandQueue) -> Result<(), String> {
     queue.enqueue(CommandType::Save);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tune(axis: SelectPID) -> CommandType {
+        let pid = PIDController { p: 1.0, i: 0.0, d: 0.0, i_limit: 0.0, pid_limit: 1.0 };
+        CommandType::TunePID(PIDTunePacket::new(axis, &pid))
+    }
+
+    #[test]
+    fn coalescing_enqueue_keeps_batch_members() {
+        let queue = CommandQueue::default();
+        queue.enqueue_batch(tune(SelectPID::Roll), 1);
+        queue.enqueue(tune(SelectPID::Pitch));
+        queue.enqueue(tune(SelectPID::Yaw));
+
+        let queued: Vec<_> = std::iter::from_fn(|| queue.dequeue()).collect();
+        assert_eq!(queued.len(), 2);
+        assert_eq!(queued[0].batch, Some(1));
+        assert_eq!(queued[1].command, tune(SelectPID::Yaw));
+    }
+
+    #[test]
+    fn batch_counts_only_its_own_acks() {
+        let mut tracker = CommandTracker::default();
+        tracker.record_sent(tune(SelectPID::Roll), None);
+        let batch = tracker.begin_batch(2, Duration::from_secs(10));
+        tracker.record_sent(tune(SelectPID::Roll), Some(batch));
+        tracker.record_sent(tune(SelectPID::Pitch), Some(batch));
+
+        // The first ACK belongs to the command sent before the batch
+        tracker.acknowledge("PID");
+        assert_eq!(tracker.batch.map(|b| b.acked), Some(0));
+        tracker.acknowledge("PID");
+        assert!(tracker.take_finished_batch().is_none());
+        tracker.acknowledge("PID");
+        let finished = tracker.take_finished_batch().expect("batch finished");
+        assert!(!finished.failed);
+        assert_eq!(finished.acked, 2);
+    }
+
+    #[test]
+    fn batch_fails_after_its_deadline() {
+        let mut tracker = CommandTracker::default();
+        tracker.begin_batch(7, Duration::ZERO);
+        tracker.expire(Duration::from_secs(10));
+        assert!(tracker.take_finished_batch().is_some_and(|b| b.failed));
+    }
+}
@@ -0,0 +1,61 @@
+// Hex dump of every chunk read from the link, for debugging the framing
+
+use chrono::Local;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const BYTES_PER_LINE: usize = 16;
+/// The writer is flushed at most this often so capturing never stalls the read loop
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct RawCapture {
+    pub path: PathBuf,
+    writer: BufWriter<File>,
+    started: Instant,
+    last_flush: Instant,
+}
+
+impl RawCapture {
+    /// Creates a timestamped capture file in `dir`
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("failed to create {:?}: {}", dir, e))?;
+        let path = dir.join(format!("raw_{}.txt", Local::now().format("%Y%m%d_%H%M%S")));
+        let file = File::create(&path).map_err(|e| format!("failed to create {:?}: {}", path, e))?;
+        let mut writer = BufWriter::new(file);
+        let _ = writeln!(writer, "# raw capture started {}", Local::now().to_rfc3339());
+        let _ = writeln!(writer, "# seconds    | hex                                             | ascii");
+        let now = Instant::now();
+        Ok(Self {
+            path,
+            writer,
+            started: now,
+            last_flush: now,
+        })
+    }
+
+    /// Appends one read chunk as hex + ASCII lines stamped with seconds since the capture began
+    pub fn write_chunk(&mut self, bytes: &[u8]) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        for (i, line) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+            let hex: String = line.iter().map(|b| format!("{:02X} ", b)).collect();
+            let ascii: String = line
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            let stamp = if i == 0 { format!("{:12.6}", elapsed) } else { " ".repeat(12) };
+            let _ = writeln!(self.writer, "{} | {:<48}| {}", stamp, hex, ascii);
+        }
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            let _ = self.writer.flush();
+            self.last_flush = Instant::now();
+        }
+    }
+}
+
+impl Drop for RawCapture {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
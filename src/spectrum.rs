@@ -0,0 +1,197 @@
+// Frequency content of attitude signals, for spotting frame resonances and prop
+// imbalance, and the lag between commanded and measured values. Telemetry arrives at
+// uneven intervals, so samples are resampled onto a uniform time grid first.
+
+use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+
+/// Fewer samples than this give too coarse a spectrum to be useful
+const MIN_SAMPLES: usize = 16;
+/// Longest delay searched for between a command and its response
+const MAX_LAG_SECS: f64 = 0.5;
+/// Below this normalised correlation there is no clear response to time
+const MIN_LAG_CORRELATION: f64 = 0.3;
+/// Longest uniform grid resampled; a timestamp gap or wrap that would need more points
+/// than this makes the window unusable rather than a huge allocation
+const MAX_RESAMPLED_POINTS: usize = 1 << 16;
+
+/// Magnitude spectrum of `(time_secs, value)` samples as `[frequency_hz, amplitude]`
+/// points from the lowest non-zero bin up to Nyquist. Samples must be in time order.
+/// The mean is removed and a Hann window applied; amplitudes are in the input's unit.
+pub fn compute_fft(samples: &[(f64, f64)]) -> Vec<[f64; 2]> {
+    let Some(dt) = median_interval(samples) else {
+        return Vec::new();
+    };
+    let uniform = resample(samples, dt);
+    let n = uniform.len();
+    if n < MIN_SAMPLES {
+        return Vec::new();
+    }
+
+    let mean = uniform.iter().sum::<f64>() / n as f64;
+    let mut window_sum = 0.0;
+    let mut buffer: Vec<Complex<f64>> = uniform
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let hann = 0.5 - 0.5 * (std::f64::consts::TAU * i as f64 / (n - 1) as f64).cos();
+            window_sum += hann;
+            Complex::new((v - mean) * hann, 0.0)
+        })
+        .collect();
+    FftPlanner::new().plan_fft_forward(n).process(&mut buffer);
+
+    // Single-sided amplitude, corrected for the energy the window removes
+    let scale = 2.0 / window_sum;
+    let bin_hz = 1.0 / (n as f64 * dt);
+    buffer[1..=n / 2]
+        .iter()
+        .enumerate()
+        .map(|(k, c)| [(k + 1) as f64 * bin_hz, c.norm() * scale])
+        .collect()
+}
+
+/// How long the response trails the command in `(time_secs, command, response)` samples,
+/// as `(lag_ms, correlation)`. Both signals are resampled and differentiated so steps in
+/// the command line up with the response's reaction; the lag is the shift with the
+/// strongest normalised cross-correlation. None when the command is flat or nothing
+/// correlates clearly.
+pub fn estimate_lag(samples: &[(f64, f64, f64)]) -> Option<(f64, f64)> {
+    let command: Vec<(f64, f64)> = samples.iter().map(|&(t, c, _)| (t, c)).collect();
+    let response: Vec<(f64, f64)> = samples.iter().map(|&(t, _, r)| (t, r)).collect();
+    let dt = median_interval(&command)?;
+    let command = differentiate(&resample(&command, dt));
+    let response = differentiate(&resample(&response, dt));
+    let n = command.len();
+    if n < MIN_SAMPLES {
+        return None;
+    }
+
+    let max_shift = ((MAX_LAG_SECS / dt) as usize).min(n / 2);
+    let mut best: Option<(usize, f64)> = None;
+    for shift in 0..=max_shift {
+        // A shift can leave one side without any change; it just can't be the answer
+        let Some(r) = normalized_correlation(&command[..n - shift], &response[shift..]) else {
+            continue;
+        };
+        if best.is_none_or(|(_, best_r)| r > best_r) {
+            best = Some((shift, r));
+        }
+    }
+    let (shift, r) = best?;
+    (r >= MIN_LAG_CORRELATION).then_some((shift as f64 * dt * 1000.0, r))
+}
+
+fn differentiate(values: &[f64]) -> Vec<f64> {
+    values.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+/// Cross-correlation scaled to -1..1; None when either signal is flat
+fn normalized_correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let energy = a.iter().map(|x| x * x).sum::<f64>() * b.iter().map(|y| y * y).sum::<f64>();
+    (energy > 0.0).then(|| dot / energy.sqrt())
+}
+
+/// Typical sample spacing, robust to the odd dropped or duplicated sample
+fn median_interval(samples: &[(f64, f64)]) -> Option<f64> {
+    let mut intervals: Vec<f64> = samples
+        .windows(2)
+        .map(|w| w[1].0 - w[0].0)
+        .filter(|dt| *dt > 0.0)
+        .collect();
+    if intervals.is_empty() {
+        return None;
+    }
+    intervals.sort_by(f64::total_cmp);
+    Some(intervals[intervals.len() / 2])
+}
+
+/// Linearly interpolates the samples at every `dt` from the first to the last time.
+/// Empty when that takes more than `MAX_RESAMPLED_POINTS`.
+fn resample(samples: &[(f64, f64)], dt: f64) -> Vec<f64> {
+    let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+        return Vec::new();
+    };
+    let count = ((last.0 - first.0) / dt).floor() as usize + 1;
+    if count > MAX_RESAMPLED_POINTS {
+        return Vec::new();
+    }
+    let mut out = Vec::with_capacity(count);
+    let mut j = 0;
+    for i in 0..count {
+        let t = first.0 + i as f64 * dt;
+        while j + 2 < samples.len() && samples[j + 1].0 < t {
+            j += 1;
+        }
+        let (t0, v0) = samples[j];
+        let (t1, v1) = samples[(j + 1).min(samples.len() - 1)];
+        let v = if t1 > t0 { v0 + (v1 - v0) * ((t - t0) / (t1 - t0)).clamp(0.0, 1.0) } else { v0 };
+        out.push(v);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Command steps at `step_secs`, the response follows `lag_secs` later; 10 ms samples
+    fn delayed_step(duration_secs: f64, step_secs: f64, lag_secs: f64) -> Vec<(f64, f64, f64)> {
+        (0..(duration_secs * 100.0) as usize)
+            .map(|i| {
+                let t = i as f64 * 0.01;
+                let command = if t >= step_secs { 1.0 } else { 0.0 };
+                let response = if t >= step_secs + lag_secs { 1.0 } else { 0.0 };
+                (t, command, response)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lag_of_delayed_step() {
+        let (lag_ms, r) = estimate_lag(&delayed_step(2.0, 0.5, 0.12)).expect("lag estimated");
+        assert!((lag_ms - 120.0).abs() < 1.0, "lag {} ms", lag_ms);
+        assert!(r > 0.9);
+    }
+
+    #[test]
+    fn lag_survives_shifts_past_the_response() {
+        // The longest shifts leave a response window without its step
+        let (lag_ms, _) = estimate_lag(&delayed_step(0.8, 0.3, 0.05)).expect("lag estimated");
+        assert!((lag_ms - 50.0).abs() < 1.0, "lag {} ms", lag_ms);
+    }
+
+    #[test]
+    fn no_lag_for_flat_command() {
+        let samples: Vec<_> = (0..200).map(|i| (i as f64 * 0.01, 0.0, (i as f64).sin())).collect();
+        assert_eq!(estimate_lag(&samples), None);
+    }
+
+    #[test]
+    fn fft_peaks_at_sine_frequency() {
+        // 12.5 Hz sampled at 100 Hz for 2.56 s, bins about 0.4 Hz apart
+        let samples: Vec<_> = (0..256)
+            .map(|i| {
+                let t = i as f64 * 0.01;
+                (t, 3.0 * (std::f64::consts::TAU * 12.5 * t).sin() + 1.0)
+            })
+            .collect();
+        let spectrum = compute_fft(&samples);
+        let bin_hz = spectrum[0][0];
+        let peak = spectrum.iter().max_by(|a, b| a[1].total_cmp(&b[1])).unwrap();
+        assert!((peak[0] - 12.5).abs() <= bin_hz / 2.0, "peak at {} Hz", peak[0]);
+        // The Hann window loses up to ~15% between bins
+        assert!(peak[1] > 2.5 && peak[1] < 3.1, "amplitude {}", peak[1]);
+    }
+
+    #[test]
+    fn timestamp_jump_is_not_resampled() {
+        // 10 ms samples, then a u32 ms wrap worth of silence
+        let mut samples: Vec<_> = (0..100).map(|i| (i as f64 * 0.01, (i as f64).sin())).collect();
+        samples.push((u32::MAX as f64 / 1000.0, 0.0));
+        assert!(compute_fft(&samples).is_empty());
+        let with_command: Vec<_> = samples.iter().map(|&(t, v)| (t, v, v)).collect();
+        assert_eq!(estimate_lag(&with_command), None);
+    }
+}
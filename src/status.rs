@@ -0,0 +1,82 @@
+// Status and error reports from background threads. They are queued on a channel and
+// drained into the log panel each frame, tagged with the thread they came from.
+
+use bevy::log::{error, info, warn};
+use std::sync::{Mutex, mpsc};
+
+use crate::log_buffer::LogBuffer;
+use crate::telemetry::LogLevel;
+
+pub struct StatusMessage {
+    pub source: &'static str,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// Handle a background thread reports through; also mirrors each message to the app log
+#[derive(Clone)]
+pub struct StatusSender {
+    source: &'static str,
+    tx: mpsc::Sender<StatusMessage>,
+}
+
+impl StatusSender {
+    pub fn info(&self, message: impl Into<String>) {
+        let message = message.into();
+        info!("[{}] {}", self.source, message);
+        self.send(LogLevel::Info, message);
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        let message = message.into();
+        warn!("[{}] {}", self.source, message);
+        self.send(LogLevel::Warn, message);
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        let message = message.into();
+        error!("[{}] {}", self.source, message);
+        self.send(LogLevel::Error, message);
+    }
+
+    fn send(&self, level: LogLevel, message: String) {
+        // The receiver lives as long as the app, so a failed send only happens at shutdown
+        let _ = self.tx.send(StatusMessage {
+            source: self.source,
+            level,
+            message,
+        });
+    }
+}
+
+pub struct StatusChannel {
+    tx: mpsc::Sender<StatusMessage>,
+    rx: Mutex<mpsc::Receiver<StatusMessage>>,
+}
+
+impl Default for StatusChannel {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self { tx, rx: Mutex::new(rx) }
+    }
+}
+
+impl StatusChannel {
+    /// Sender whose messages are tagged with `source`, e.g. "UART"
+    pub fn sender(&self, source: &'static str) -> StatusSender {
+        StatusSender {
+            source,
+            tx: self.tx.clone(),
+        }
+    }
+
+    /// Moves every pending message into the log panel
+    pub fn drain_into(&self, buffer: &mut LogBuffer) {
+        let Ok(rx) = self.rx.lock() else {
+            return;
+        };
+        for status in rx.try_iter() {
+            buffer.push_log_level(status.level, format!("[{}] {}", status.source, status.message));
+        }
+    }
+}
@@ -1,8 +1,15 @@
 use bytemuck::{Pod, Zeroable};
 use chrono::{DateTime, Local};
-use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::config::{MAX_LOG_MESSAGES, MAX_POINTS};
+use crate::config::MAX_POINTS;
+use crate::log_buffer::{LogSender, PendingLog};
+use crate::log_rules::LogRule;
+use crate::protocol::{CommandTracker, FirmwareVersion};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum PidAxis {
@@ -11,11 +18,153 @@ pub enum PidAxis {
     Yaw,
 }
 
+impl PidAxis {
+    pub fn name(self) -> &'static str {
+        match self {
+            PidAxis::Roll => "Roll",
+            PidAxis::Pitch => "Pitch",
+            PidAxis::Yaw => "Yaw",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PidTerm {
+    P,
+    I,
+    D,
+}
+
+impl PidTerm {
+    pub fn name(self) -> &'static str {
+        match self {
+            PidTerm::P => "P",
+            PidTerm::I => "I",
+            PidTerm::D => "D",
+        }
+    }
+}
+
+/// What `DataBuffer::push` does once `MAX_POINTS` samples are stored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BufferFullPolicy {
+    /// Discard the oldest sample (rolling window)
+    #[default]
+    DropOldest,
+    /// Keep the first `MAX_POINTS` samples and ignore new ones
+    StopRecording,
+    /// Halve the stored resolution and keep every Nth new sample
+    Downsample,
+}
+
+impl BufferFullPolicy {
+    pub const ALL: [BufferFullPolicy; 3] = [
+        BufferFullPolicy::DropOldest,
+        BufferFullPolicy::StopRecording,
+        BufferFullPolicy::Downsample,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BufferFullPolicy::DropOldest => "Drop oldest",
+            BufferFullPolicy::StopRecording => "Stop recording",
+            BufferFullPolicy::Downsample => "Downsample",
+        }
+    }
+}
+
+/// What the plots use as their x coordinate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum XAxisMode {
+    /// Firmware timestamp in seconds
+    #[default]
+    Time,
+    /// Running sample count, for when firmware timing is unreliable
+    SampleIndex,
+}
+
+impl XAxisMode {
+    pub fn x(self, d: &TelemetryData) -> f64 {
+        match self {
+            XAxisMode::Time => d.timestamp as f64 / 1000.0,
+            XAxisMode::SampleIndex => d.sample_index as f64,
+        }
+    }
+}
+
+/// How yaw is drawn on the attitude plot
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum YawDisplay {
+    /// Accumulated through turns, without jumps where the heading crosses ±180°
+    #[default]
+    Continuous,
+    /// Folded into [-180°, 180°)
+    Wrapped,
+}
+
+impl YawDisplay {
+    pub const ALL: [YawDisplay; 2] = [YawDisplay::Continuous, YawDisplay::Wrapped];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            YawDisplay::Continuous => "Continuous",
+            YawDisplay::Wrapped => "Wrapped ±180°",
+        }
+    }
+}
+
+/// Folds an angle in radians into [-π, π)
+pub fn wrap_angle(rad: f64) -> f64 {
+    (rad + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU) - std::f64::consts::PI
+}
+
+/// Unit angles and angular rates are displayed in. Telemetry angles (roll, pitch,
+/// yaw, setpoints) and gyro rates are always radians internally; convert only for display.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AngleUnit {
+    #[default]
+    Degrees,
+    Radians,
+}
+
+impl AngleUnit {
+    /// Converts a value in radians (or rad/s) to this unit
+    pub fn convert(self, rad: f64) -> f64 {
+        match self {
+            AngleUnit::Degrees => rad.to_degrees(),
+            AngleUnit::Radians => rad,
+        }
+    }
+
+    /// Unit label for axis titles, e.g. "deg" or "rad"
+    pub fn name(self) -> &'static str {
+        match self {
+            AngleUnit::Degrees => "deg",
+            AngleUnit::Radians => "rad",
+        }
+    }
+
+    /// Formats an angle given in radians with its unit suffix
+    pub fn format(self, rad: f32) -> String {
+        match self {
+            AngleUnit::Degrees => format!("{:.2}°", rad.to_degrees()),
+            AngleUnit::Radians => format!("{:.3} rad", rad),
+        }
+    }
+}
+
+/// One decoded telemetry sample.
+///
+/// Angles (`roll`/`pitch`/`yaw` and the `input_*` setpoints) are radians and gyro rates
+/// are rad/s, exactly as the firmware sends them. Everything downstream (the 3D model,
+/// alarms, exports) works in radians; only display code converts, via `AngleUnit`.
 #[derive(Clone, Debug)]
 pub struct TelemetryData {
     pub timestamp: u32,
+    /// Position in the stream since the buffer was last cleared, assigned by `DataBuffer::push`
+    pub sample_index: u64,
     pub clock_time: DateTime<Local>,
-    // Attitude
+    // Attitude (rad)
     pub roll: f32,
     pub pitch: f32,
     pub yaw: f32,
@@ -46,11 +195,82 @@ pub struct TelemetryData {
     pub motor2: f32,
     pub motor3: f32,
     pub motor4: f32,
-    // Commanded setpoints from pilot sticks
+    // Commanded setpoints from pilot sticks (throttle 0.0-1.0, angles in rad)
     pub input_throttle: f32,
     pub input_roll: f32,
     pub input_pitch: f32,
     pub input_yaw: f32,
+    // GPS fix, only sent by firmware with the GPS extension (degrees)
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    pub sats: u8,
+    /// Firmware time missing before this sample, when gap detection flagged one (ms)
+    pub gap_before_ms: Option<u32>,
+}
+
+impl TelemetryData {
+    /// Names of all numeric fields accepted by `field`, in packet order
+    pub const FIELD_NAMES: [&'static str; 27] = [
+        "roll", "pitch", "yaw",
+        "roll_p", "roll_i", "roll_d",
+        "pitch_p", "pitch_i", "pitch_d",
+        "yaw_p", "yaw_i", "yaw_d",
+        "gyro_x", "gyro_y", "gyro_z",
+        "vel_x", "vel_y", "vel_z",
+        "height",
+        "motor1", "motor2", "motor3", "motor4",
+        "input_throttle", "input_roll", "input_pitch", "input_yaw",
+    ];
+
+    /// Reported PID term output for one axis
+    pub fn pid_term(&self, axis: PidAxis, term: PidTerm) -> f32 {
+        match (axis, term) {
+            (PidAxis::Roll, PidTerm::P) => self.roll_p,
+            (PidAxis::Roll, PidTerm::I) => self.roll_i,
+            (PidAxis::Roll, PidTerm::D) => self.roll_d,
+            (PidAxis::Pitch, PidTerm::P) => self.pitch_p,
+            (PidAxis::Pitch, PidTerm::I) => self.pitch_i,
+            (PidAxis::Pitch, PidTerm::D) => self.pitch_d,
+            (PidAxis::Yaw, PidTerm::P) => self.yaw_p,
+            (PidAxis::Yaw, PidTerm::I) => self.yaw_i,
+            (PidAxis::Yaw, PidTerm::D) => self.yaw_d,
+        }
+    }
+
+    /// Looks up a numeric field by name
+    pub fn field(&self, name: &str) -> Option<f64> {
+        let value = match name {
+            "roll" => self.roll,
+            "pitch" => self.pitch,
+            "yaw" => self.yaw,
+            "roll_p" => self.roll_p,
+            "roll_i" => self.roll_i,
+            "roll_d" => self.roll_d,
+            "pitch_p" => self.pitch_p,
+            "pitch_i" => self.pitch_i,
+            "pitch_d" => self.pitch_d,
+            "yaw_p" => self.yaw_p,
+            "yaw_i" => self.yaw_i,
+            "yaw_d" => self.yaw_d,
+            "gyro_x" => self.gyro_x,
+            "gyro_y" => self.gyro_y,
+            "gyro_z" => self.gyro_z,
+            "vel_x" => self.vel_x,
+            "vel_y" => self.vel_y,
+            "vel_z" => self.vel_z,
+            "height" => self.height,
+            "motor1" => self.motor1,
+            "motor2" => self.motor2,
+            "motor3" => self.motor3,
+            "motor4" => self.motor4,
+            "input_throttle" => self.input_throttle,
+            "input_roll" => self.input_roll,
+            "input_pitch" => self.input_pitch,
+            "input_yaw" => self.input_yaw,
+            _ => return None,
+        };
+        Some(value as f64)
+    }
 }
 
 #[repr(C, packed)]
@@ -99,6 +319,7 @@ impl From<&TelemetryPacket> for TelemetryData {
     fn from(packet: &TelemetryPacket) -> Self {
         Self {
             timestamp: packet.timestamp_ms,
+            sample_index: 0,
             clock_time: Local::now(),
             roll: packet.roll,
             pitch: packet.pitch,
@@ -127,60 +348,1126 @@ impl From<&TelemetryPacket> for TelemetryData {
             input_roll: packet.input_roll,
             input_pitch: packet.input_pitch,
             input_yaw: packet.input_yaw,
+            lat: None,
+            lon: None,
+            sats: 0,
+            gap_before_ms: None,
+        }
+    }
+}
+
+/// Optional trailer appended to the telemetry payload by GPS-equipped firmware
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct GpsExtension {
+    lat_deg: f64,
+    lon_deg: f64,
+    sats: u8,
+}
+
+impl TelemetryPacket {
+    /// Payload lengths accepted for a telemetry frame: base packet, or base + GPS
+    pub const LEN: usize = std::mem::size_of::<TelemetryPacket>();
+    pub const LEN_WITH_GPS: usize = Self::LEN + std::mem::size_of::<GpsExtension>();
+}
+
+impl TelemetryData {
+    /// Decodes a telemetry frame payload, with or without the GPS extension
+    pub fn from_payload(payload: &[u8]) -> Option<Self> {
+        let (base, extension) = payload.split_at_checked(TelemetryPacket::LEN)?;
+        let mut data: TelemetryData = bytemuck::try_from_bytes::<TelemetryPacket>(base).ok()?.into();
+        if !extension.is_empty() {
+            let gps = bytemuck::try_from_bytes::<GpsExtension>(extension).ok()?;
+            // Firmware reports 0 satellites and zeroed coordinates until it has a fix
+            if gps.sats > 0 {
+                data.lat = Some(gps.lat_deg);
+                data.lon = Some(gps.lon_deg);
+            }
+            data.sats = gps.sats;
+        }
+        Some(data)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogLevel {
+    #[default]
+    Info,
+    Warn,
+    Error,
+    /// Echo of a command the GUI sent to the flight controller
+    Sent,
+}
+
+impl LogLevel {
+    /// Maps a firmware level prefix (case-insensitive) to a level
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix.to_ascii_uppercase().as_str() {
+            "INFO" => Some(LogLevel::Info),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "ERR" | "ERROR" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Info => "Info",
+            LogLevel::Warn => "Warn",
+            LogLevel::Error => "Error",
+            LogLevel::Sent => "Sent",
+        }
+    }
+}
+
+/// One `ESC:` line: per-motor ESC temperature (°C) and current (A), motors 1-4
+#[derive(Clone, Debug)]
+pub struct EscTelemetry {
+    pub temps: [f32; 4],
+    pub currents: [f32; 4],
+    /// ESC lines carry no firmware timestamp, so they are placed by arrival time
+    pub clock_time: DateTime<Local>,
+}
+
+/// What the link delivered, before TELEM/LOG parsing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RawPacketKind {
+    Line,
+    Frame { pkt_type: u8, crc_ok: bool },
+}
+
+/// A complete text line or binary frame as it arrived, for the raw packet view
+#[derive(Clone, Debug)]
+pub struct RawPacket {
+    pub clock_time: DateTime<Local>,
+    pub kind: RawPacketKind,
+    /// Bytes on the wire: the trimmed line, or the frame payload
+    pub len: usize,
+    /// The line itself, or the frame payload as hex
+    pub text: String,
+}
+
+impl RawPacket {
+    pub fn line(line: &str) -> Self {
+        Self {
+            clock_time: Local::now(),
+            kind: RawPacketKind::Line,
+            len: line.len(),
+            text: line.to_string(),
+        }
+    }
+
+    pub fn frame(pkt_type: u8, payload: &[u8], crc_ok: bool) -> Self {
+        Self {
+            clock_time: Local::now(),
+            kind: RawPacketKind::Frame { pkt_type, crc_ok },
+            len: payload.len(),
+            text: payload.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "),
+        }
+    }
+
+    pub fn kind_label(&self) -> String {
+        match self.kind {
+            RawPacketKind::Line => "line".to_string(),
+            RawPacketKind::Frame { pkt_type, crc_ok: true } => format!("frame 0x{:02X}", pkt_type),
+            RawPacketKind::Frame { pkt_type, crc_ok: false } => format!("frame 0x{:02X} (bad CRC)", pkt_type),
         }
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct LogMessage {
+    /// Increases by one per message, so the log view can find its place after old lines are dropped
+    pub seq: u64,
     pub _timestamp: f64,
     pub clock_time: DateTime<Local>,
+    pub level: LogLevel,
     pub message: String,
 }
 
+/// Identifies one run of the application in exported files
+#[derive(Clone, Debug)]
+pub struct SessionInfo {
+    pub id: uuid::Uuid,
+    pub started: DateTime<Local>,
+}
+
+impl SessionInfo {
+    pub fn new() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            started: Local::now(),
+        }
+    }
+}
+
+/// Summary of one signal over a range of samples
+#[derive(Clone, Copy, Debug)]
+pub struct SeriesStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub current: f64,
+}
+
+/// Samples closer together than this reuse the previous rate instead of dividing by ~0
+const MIN_RATE_DT_SECS: f64 = 0.001;
+/// Exponential smoothing factor applied to finite-difference rates
+const RATE_SMOOTHING: f64 = 0.3;
+/// Per-sample smoothing of the gyro low-pass; what it filters out is treated as vibration
+const VIBRATION_LOWPASS: f64 = 0.1;
+/// Per-sample smoothing of the mean square of the high-frequency residual
+const VIBRATION_ENERGY_SMOOTHING: f64 = 0.02;
+/// The vibration alarm re-arms once the level drops below this fraction of the threshold
+const VIBRATION_REARM_RATIO: f64 = 0.8;
+/// Settings for the setpoint tracking alarm
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeviationAlarm {
+    /// Allowed |attitude - setpoint| (rad)
+    pub margin: f32,
+    /// How long the margin must be exceeded before warning (ms of firmware time)
+    pub hold_ms: u32,
+}
+
+/// Settings for the oscillation detector
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OscillationDetector {
+    /// Smallest peak |attitude - setpoint| between sign changes that counts (rad)
+    pub amplitude: f32,
+    /// Span the sign changes must fall within (ms of firmware time)
+    pub window_ms: u32,
+}
+
+/// Sign changes of the attitude error within the window that count as oscillating (three cycles)
+const OSCILLATION_MIN_CROSSINGS: usize = 6;
+/// Oscillation episodes kept for the attitude plot
+const MAX_OSCILLATIONS: usize = 100;
+
+/// Zero-crossing tracking of one axis' attitude error
+#[derive(Clone, Debug, Default)]
+struct OscillationTrack {
+    positive: Option<bool>,
+    /// Largest |error| since the last sign change
+    peak: f32,
+    /// Firmware timestamp and sample index of recent large-enough sign changes
+    crossings: VecDeque<(u32, u64)>,
+    /// Index into `oscillations` of the episode still in progress
+    active: Option<usize>,
+}
+
+/// A stretch of sustained oscillation on one axis
+#[derive(Clone, Debug)]
+pub struct Oscillation {
+    pub axis: &'static str,
+    pub start_timestamp: u32,
+    pub start_index: u64,
+    pub end_timestamp: u32,
+    pub end_index: u64,
+}
+
+impl Oscillation {
+    /// Start and end on the plots' x axis
+    pub fn span(&self, x_mode: XAxisMode) -> (f64, f64) {
+        match x_mode {
+            XAxisMode::Time => (self.start_timestamp as f64 / 1000.0, self.end_timestamp as f64 / 1000.0),
+            XAxisMode::SampleIndex => (self.start_index as f64, self.end_index as f64),
+        }
+    }
+}
+
+/// An interval this many times the usual one counts as a gap
+const GAP_INTERVAL_FACTOR: f64 = 2.5;
+/// Smoothing of the usual sample interval used for gap detection
+const GAP_INTERVAL_SMOOTHING: f64 = 0.05;
+/// The usual interval starts as the median of this many first intervals, so a gap
+/// right after connecting doesn't become the reference
+const GAP_BASELINE_INTERVALS: usize = 5;
+
+/// Parse errors are summarised in the log at most this often
+const PARSE_ERROR_REPORT_SECS: f32 = 5.0;
+/// Parse error messages kept for bug reports
+const RECENT_PARSE_ERRORS: usize = 20;
+
+/// While telemetry streams, a cached series is rebuilt at most this often
+const SERIES_REBUILD_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Plot points shared between the series cache and the frame drawing them
+pub type SeriesPoints = Arc<[[f64; 2]]>;
+
+/// A plot series as last built, with what it was built from
+struct CachedSeries {
+    generation: u64,
+    view: u64,
+    built_at: Instant,
+    points: SeriesPoints,
+}
+
 pub struct DataBuffer {
     pub data: VecDeque<TelemetryData>,
-    pub logs: VecDeque<LogMessage>,
-    start_time: std::time::Instant,
+    /// ESC temperature/current samples, oldest first
+    pub esc: VecDeque<EscTelemetry>,
+    /// Bumped whenever `data` changes, so cached plot series know to rebuild
+    generation: u64,
+    /// Plot series by id; the UI repaints continuously, but data arrives far less often
+    series_cache: RefCell<HashMap<String, CachedSeries>>,
+    /// Log panel, locked separately; messages pushed here are queued for it
+    log: LogSender,
+    pub session: SessionInfo,
+    /// When the most recent telemetry sample arrived
+    pub last_sample_at: Option<std::time::Instant>,
+    /// Arrival times of samples received within the last second
+    recent_arrivals: VecDeque<std::time::Instant>,
+    full_policy: BufferFullPolicy,
+    /// Only every `downsample_stride`-th sample is stored (Downsample policy)
+    pub downsample_stride: usize,
+    downsample_skipped: usize,
+    next_sample_index: u64,
+    /// Continue the time base after the firmware timestamp jumps backwards (e.g. reboot)
+    pub rebase_on_rewind: bool,
+    timestamp_offset: u32,
+    last_raw_timestamp: Option<u32>,
+    pub timestamp_rewinds: usize,
+    /// Warn about and count duplicate timestamps and jumps much longer than the usual interval
+    pub detect_gaps: bool,
+    pub timestamp_gaps: usize,
+    pub duplicate_timestamps: usize,
+    /// Smoothed firmware interval between samples (ms), the reference for spotting gaps
+    typical_interval_ms: Option<f64>,
+    /// First intervals (ms) seen, until there are enough to seed `typical_interval_ms`
+    gap_baseline: Vec<u32>,
+    log_rules: Vec<LogRule>,
+    /// Previous incoming sample, kept even when the buffer skips storing it
+    rule_prev: Option<TelemetryData>,
+    deviation_alarm: Option<DeviationAlarm>,
+    /// Firmware timestamp at which roll/pitch left the margin, and whether that was reported
+    deviation_since: [Option<(u32, bool)>; 2],
+    oscillation_detector: Option<OscillationDetector>,
+    oscillation_tracks: [OscillationTrack; 2],
+    /// Detected oscillation episodes, oldest first
+    pub oscillations: VecDeque<Oscillation>,
+    /// Notes dropped on the timeline: (firmware time in s, label), oldest first
+    pub events: VecDeque<(f64, String)>,
+    /// Sent commands awaiting an ACK from the firmware
+    pub commands: CommandTracker,
+    /// Reply to the version query sent on connect; None until it arrives
+    pub firmware_version: Option<FirmwareVersion>,
+    /// Corrupt frames and unrecognised lines received since the last clear
+    pub parse_error_count: usize,
+    /// Most recent parse error messages, oldest first
+    pub recent_parse_errors: VecDeque<String>,
+    parse_errors_unreported: usize,
+    last_parse_report: Option<std::time::Instant>,
+    /// RMS of the high-frequency part of the gyro signal (deg/s), a vibration indicator
+    pub vibration: f64,
+    gyro_lowpass: Option<[f64; 3]>,
+    vibration_energy: f64,
+    vibration_threshold: Option<f64>,
+    vibration_alarm_active: bool,
+    /// Every incoming sample, queued regardless of the full policy while a consumer
+    /// (headless logging) is recording; None when nobody takes them
+    recorded: Option<Vec<TelemetryData>>,
 }
 
 impl DataBuffer {
-    pub fn new() -> Self {
+    pub fn new(log: LogSender) -> Self {
         Self {
             data: VecDeque::with_capacity(MAX_POINTS),
-            logs: VecDeque::with_capacity(MAX_LOG_MESSAGES),
-            start_time: std::time::Instant::now(),
+            esc: VecDeque::new(),
+            generation: 0,
+            series_cache: RefCell::new(HashMap::new()),
+            log,
+            session: SessionInfo::new(),
+            last_sample_at: None,
+            recent_arrivals: VecDeque::new(),
+            full_policy: BufferFullPolicy::default(),
+            downsample_stride: 1,
+            downsample_skipped: 0,
+            next_sample_index: 0,
+            rebase_on_rewind: true,
+            timestamp_offset: 0,
+            last_raw_timestamp: None,
+            timestamp_rewinds: 0,
+            detect_gaps: false,
+            timestamp_gaps: 0,
+            duplicate_timestamps: 0,
+            typical_interval_ms: None,
+            gap_baseline: Vec::with_capacity(GAP_BASELINE_INTERVALS),
+            log_rules: Vec::new(),
+            rule_prev: None,
+            deviation_alarm: None,
+            deviation_since: [None; 2],
+            oscillation_detector: None,
+            oscillation_tracks: Default::default(),
+            oscillations: VecDeque::new(),
+            events: VecDeque::new(),
+            commands: CommandTracker::default(),
+            firmware_version: None,
+            parse_error_count: 0,
+            recent_parse_errors: VecDeque::new(),
+            parse_errors_unreported: 0,
+            last_parse_report: None,
+            vibration: 0.0,
+            gyro_lowpass: None,
+            vibration_energy: 0.0,
+            vibration_threshold: None,
+            vibration_alarm_active: false,
+            recorded: None,
         }
     }
 
+    /// Starts queueing every incoming sample for `take_recorded`, independent of what the
+    /// plot buffer keeps
+    pub fn start_recording(&mut self) {
+        self.recorded.get_or_insert_with(Vec::new);
+    }
+
+    /// Samples queued since the last call, oldest first
+    pub fn take_recorded(&mut self) -> Vec<TelemetryData> {
+        self.recorded.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Empties the plot buffer and resets everything derived from it: cached series,
+    /// the sample rate, time base tracking, parse-error counts and alarms
     pub fn clear_data(&mut self) {
         self.data.clear();
+        self.esc.clear();
+        self.generation += 1;
+        self.series_cache.get_mut().clear();
+        self.last_sample_at = None;
+        self.recent_arrivals.clear();
+        self.downsample_stride = 1;
+        self.downsample_skipped = 0;
+        self.next_sample_index = 0;
+        self.timestamp_offset = 0;
+        self.last_raw_timestamp = None;
+        self.timestamp_rewinds = 0;
+        self.timestamp_gaps = 0;
+        self.duplicate_timestamps = 0;
+        self.typical_interval_ms = None;
+        self.gap_baseline.clear();
+        self.rule_prev = None;
+        self.parse_error_count = 0;
+        self.recent_parse_errors.clear();
+        self.parse_errors_unreported = 0;
+        self.last_parse_report = None;
+        self.vibration = 0.0;
+        self.gyro_lowpass = None;
+        self.vibration_energy = 0.0;
+        self.vibration_alarm_active = false;
+        self.deviation_since = [None; 2];
+        self.oscillation_tracks = Default::default();
+        self.oscillations.clear();
+        self.events.clear();
     }
 
-    pub fn clear_logs(&mut self) {
-        self.logs.clear();
+    /// Changes the full-buffer policy, restoring full-rate recording if it changed
+    pub fn set_full_policy(&mut self, policy: BufferFullPolicy) {
+        if self.full_policy != policy {
+            self.full_policy = policy;
+            self.downsample_stride = 1;
+            self.downsample_skipped = 0;
+        }
+    }
+
+    /// Samples within the last `window_secs` (by firmware timestamp) of the newest sample,
+    /// or all samples when `window_secs` is None
+    pub fn visible(&self, window_secs: Option<f32>) -> std::collections::vec_deque::Iter<'_, TelemetryData> {
+        let start = match (window_secs, self.data.back()) {
+            (Some(window), Some(last)) => {
+                let cutoff = last.timestamp as f64 / 1000.0 - window as f64;
+                self.data.partition_point(|d| (d.timestamp as f64 / 1000.0) < cutoff)
+            }
+            _ => 0,
+        };
+        self.data.range(start..)
+    }
+
+    /// The stored sample whose plot x is closest to `x`
+    pub fn nearest_sample(&self, x_mode: XAxisMode, x: f64) -> Option<&TelemetryData> {
+        let i = self.data.partition_point(|d| x_mode.x(d) < x);
+        let before = i.checked_sub(1).and_then(|j| self.data.get(j));
+        match (before, self.data.get(i)) {
+            (Some(b), Some(a)) => Some(if x - x_mode.x(b) <= x_mode.x(a) - x { b } else { a }),
+            (b, a) => b.or(a),
+        }
+    }
+
+    /// Smoothed finite-difference rate (deg/s) of an angle in radians, paired with the
+    /// sample it was computed at. The first visible sample has no rate and is skipped.
+    /// Deltas are wrapped to ±π so yaw crossing ±180° doesn't produce a spike.
+    pub fn angle_rate_data(
+        &self,
+        window_secs: Option<f32>,
+        angle: impl Fn(&TelemetryData) -> f32,
+    ) -> Vec<(&TelemetryData, f64)> {
+        let mut out = Vec::new();
+        let mut prev: Option<&TelemetryData> = None;
+        let mut rate = 0.0;
+        for d in self.visible(window_secs) {
+            if let Some(p) = prev {
+                let dt = (d.timestamp as f64 - p.timestamp as f64) / 1000.0;
+                if dt >= MIN_RATE_DT_SECS {
+                    let delta = wrap_angle((angle(d) - angle(p)) as f64);
+                    let raw = delta.to_degrees() / dt;
+                    rate += RATE_SMOOTHING * (raw - rate);
+                }
+                out.push((d, rate));
+            }
+            prev = Some(d);
+        }
+        out
+    }
+
+    pub fn get_roll_rate_data(&self, window_secs: Option<f32>) -> Vec<(&TelemetryData, f64)> {
+        self.angle_rate_data(window_secs, |d| d.roll)
+    }
+
+    pub fn get_pitch_rate_data(&self, window_secs: Option<f32>) -> Vec<(&TelemetryData, f64)> {
+        self.angle_rate_data(window_secs, |d| d.pitch)
+    }
+
+    pub fn get_yaw_rate_data(&self, window_secs: Option<f32>) -> Vec<(&TelemetryData, f64)> {
+        self.angle_rate_data(window_secs, |d| d.yaw)
+    }
+
+    /// Min/max/mean/current of a signal over the same samples `visible` returns
+    pub fn stats(&self, window_secs: Option<f32>, value: impl Fn(&TelemetryData) -> f64) -> Option<SeriesStats> {
+        let mut count = 0usize;
+        let mut sum = 0.0;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        let mut current = 0.0;
+        for d in self.visible(window_secs) {
+            let v = value(d);
+            count += 1;
+            sum += v;
+            min = min.min(v);
+            max = max.max(v);
+            current = v;
+        }
+        (count > 0).then(|| SeriesStats {
+            min,
+            max,
+            mean: sum / count as f64,
+            current,
+        })
+    }
+
+    /// Samples received during the last second
+    pub fn telemetry_rate_hz(&self) -> usize {
+        self.recent_arrivals
+            .iter()
+            .filter(|t| t.elapsed().as_secs_f32() <= 1.0)
+            .count()
+    }
+
+    /// True when the StopRecording policy is discarding new samples
+    pub fn is_recording_stopped(&self) -> bool {
+        self.full_policy == BufferFullPolicy::StopRecording && self.data.len() >= MAX_POINTS
     }
 
     pub fn push(&mut self, mut telem: TelemetryData) {
         telem.clock_time = Local::now();
+        self.apply_time_base(&mut telem);
+        telem.sample_index = self.next_sample_index;
+        self.next_sample_index += 1;
+        let now = std::time::Instant::now();
+        self.last_sample_at = Some(now);
+        self.recent_arrivals.push_back(now);
+        while self
+            .recent_arrivals
+            .front()
+            .is_some_and(|t| now.duration_since(*t).as_secs_f32() > 1.0)
+        {
+            self.recent_arrivals.pop_front();
+        }
+        self.evaluate_log_rules(&telem);
+        self.update_vibration(&telem);
+        self.check_setpoint_deviation(&telem);
+        self.detect_oscillation(&telem);
+        if let Some(recorded) = &mut self.recorded {
+            recorded.push(telem.clone());
+        }
+
+        if self.downsample_stride > 1 {
+            self.downsample_skipped += 1;
+            if self.downsample_skipped < self.downsample_stride {
+                return;
+            }
+            self.downsample_skipped = 0;
+        }
 
         if self.data.len() >= MAX_POINTS {
-            self.data.pop_front();
+            match self.full_policy {
+                BufferFullPolicy::DropOldest => {
+                    self.data.pop_front();
+                }
+                BufferFullPolicy::StopRecording => return,
+                BufferFullPolicy::Downsample => {
+                    let mut index = 0;
+                    self.data.retain(|_| {
+                        index += 1;
+                        index % 2 == 1
+                    });
+                    self.downsample_stride *= 2;
+                }
+            }
         }
         self.data.push_back(telem);
+        self.generation += 1;
+    }
+
+    pub fn push_esc(&mut self, esc: EscTelemetry) {
+        if self.esc.len() >= MAX_POINTS {
+            self.esc.pop_front();
+        }
+        self.esc.push_back(esc);
+    }
+
+    /// ESC samples that arrived within `window_secs` of the newest one, or all when None
+    pub fn visible_esc(&self, window_secs: Option<f32>) -> std::collections::vec_deque::Iter<'_, EscTelemetry> {
+        let start = match (window_secs, self.esc.back()) {
+            (Some(window), Some(last)) => {
+                let cutoff = last.clock_time - chrono::Duration::milliseconds((window * 1000.0) as i64);
+                self.esc.partition_point(|e| e.clock_time < cutoff)
+            }
+            _ => 0,
+        };
+        self.esc.range(start..)
+    }
+
+    /// Returns the series cached under `id`, calling `build` only if it was built for a
+    /// different `view` fingerprint, or samples changed and `SERIES_REBUILD_INTERVAL` passed.
+    /// A cache hit shares the points without copying them.
+    pub fn cached_series(&self, id: &str, view: u64, build: impl FnOnce() -> Vec<[f64; 2]>) -> SeriesPoints {
+        let cache = self.series_cache.borrow();
+        let fresh = cache.get(id).filter(|cached| {
+            cached.view == view
+                && (cached.generation == self.generation || cached.built_at.elapsed() < SERIES_REBUILD_INTERVAL)
+        });
+        if let Some(cached) = fresh {
+            return Arc::clone(&cached.points);
+        }
+        drop(cache);
+        let points: SeriesPoints = build().into();
+        self.series_cache.borrow_mut().insert(
+            id.to_string(),
+            CachedSeries {
+                generation: self.generation,
+                view,
+                built_at: Instant::now(),
+                points: Arc::clone(&points),
+            },
+        );
+        points
+    }
+
+    pub fn set_log_rules(&mut self, rules: &[LogRule]) {
+        if self.log_rules != rules {
+            self.log_rules = rules.to_vec();
+        }
+    }
+
+    /// Logs every rule that fires between the previous incoming sample and `next`
+    fn evaluate_log_rules(&mut self, next: &TelemetryData) {
+        let prev = self.rule_prev.replace(next.clone());
+        let Some(prev) = prev else {
+            return;
+        };
+        let fired: Vec<String> = self
+            .log_rules
+            .iter()
+            .filter_map(|rule| rule.evaluate(&prev, next))
+            .collect();
+        for message in fired {
+            self.push_log(message);
+        }
+    }
+
+    /// Sets the vibration level that raises a warning, or None to disable the alarm
+    pub fn set_vibration_threshold(&mut self, threshold: Option<f64>) {
+        self.vibration_threshold = threshold;
+    }
+
+    /// Updates the vibration level from the residual of a gyro low-pass filter and
+    /// warns once each time it rises above the threshold
+    fn update_vibration(&mut self, telem: &TelemetryData) {
+        let gyro = [telem.gyro_x, telem.gyro_y, telem.gyro_z].map(|g| (g as f64).to_degrees());
+        let lowpass = self.gyro_lowpass.get_or_insert(gyro);
+        let mut residual_sq = 0.0;
+        for (lp, g) in lowpass.iter_mut().zip(gyro) {
+            *lp += VIBRATION_LOWPASS * (g - *lp);
+            residual_sq += (g - *lp).powi(2);
+        }
+        self.vibration_energy += VIBRATION_ENERGY_SMOOTHING * (residual_sq - self.vibration_energy);
+        self.vibration = self.vibration_energy.sqrt();
+
+        let Some(threshold) = self.vibration_threshold else {
+            self.vibration_alarm_active = false;
+            return;
+        };
+        if !self.vibration_alarm_active && self.vibration > threshold {
+            self.vibration_alarm_active = true;
+            self.push_log_level(
+                LogLevel::Warn,
+                format!("High vibration: {:.1} deg/s (threshold {:.1})", self.vibration, threshold),
+            );
+        } else if self.vibration_alarm_active && self.vibration < threshold * VIBRATION_REARM_RATIO {
+            self.vibration_alarm_active = false;
+        }
+    }
+
+    /// True while the vibration level is above the alarm threshold
+    pub fn is_vibration_high(&self) -> bool {
+        self.vibration_alarm_active
+    }
+
+    pub fn set_deviation_alarm(&mut self, alarm: Option<DeviationAlarm>) {
+        if self.deviation_alarm != alarm {
+            self.deviation_alarm = alarm;
+            self.deviation_since = [None; 2];
+        }
+    }
+
+    /// Warns once per episode when roll or pitch stays further than the margin
+    /// from its setpoint for longer than the hold time
+    fn check_setpoint_deviation(&mut self, telem: &TelemetryData) {
+        let Some(alarm) = self.deviation_alarm else {
+            return;
+        };
+        let axes = [
+            ("Roll", telem.roll, telem.input_roll),
+            ("Pitch", telem.pitch, telem.input_pitch),
+        ];
+        for (i, (name, measured, setpoint)) in axes.into_iter().enumerate() {
+            let error = measured - setpoint;
+            if error.abs() <= alarm.margin {
+                self.deviation_since[i] = None;
+                continue;
+            }
+            let (since, reported) = *self.deviation_since[i].get_or_insert((telem.timestamp, false));
+            let held = telem.timestamp.saturating_sub(since);
+            if !reported && held >= alarm.hold_ms {
+                self.deviation_since[i] = Some((since, true));
+                self.push_log_level(
+                    LogLevel::Warn,
+                    format!(
+                        "{} not tracking setpoint: {:.1}° off for {} ms",
+                        name,
+                        error.to_degrees(),
+                        held
+                    ),
+                );
+            }
+        }
+    }
+
+    /// True while an axis has been off its setpoint for longer than the hold time
+    pub fn is_deviation_alarm_active(&self) -> bool {
+        self.deviation_since.iter().any(|d| matches!(d, Some((_, true))))
+    }
+
+    /// Drops a labeled marker at the newest sample's time and notes it in the log;
+    /// returns false when there is no telemetry to place it on
+    pub fn add_event(&mut self, label: String) -> bool {
+        let Some(last) = self.data.back() else {
+            return false;
+        };
+        let time = last.timestamp as f64 / 1000.0;
+        self.push_log(format!("Marker at {:.2} s: {}", time, label));
+        self.events.push_back((time, label));
+        true
+    }
+
+    /// Markers positioned on the plots' x axis. By sample index they sit on the first
+    /// sample at or after the marker, so markers older than the buffer are left out.
+    pub fn event_markers(&self, x_mode: XAxisMode) -> Vec<(f64, &str)> {
+        self.events
+            .iter()
+            .filter_map(|(time, label)| {
+                let x = match x_mode {
+                    XAxisMode::Time => *time,
+                    XAxisMode::SampleIndex => {
+                        let first = self.data.front()?;
+                        if (first.timestamp as f64 / 1000.0) > *time {
+                            return None;
+                        }
+                        let i = self.data.partition_point(|d| (d.timestamp as f64 / 1000.0) < *time);
+                        self.data.get(i).or(self.data.back())?.sample_index as f64
+                    }
+                };
+                Some((x, label.as_str()))
+            })
+            .collect()
+    }
+
+    pub fn set_oscillation_detector(&mut self, detector: Option<OscillationDetector>) {
+        if self.oscillation_detector != detector {
+            self.oscillation_detector = detector;
+            self.oscillation_tracks = Default::default();
+        }
+    }
+
+    /// Flags roll or pitch error that keeps changing sign with a large enough swing,
+    /// recording each episode and logging when one starts
+    fn detect_oscillation(&mut self, telem: &TelemetryData) {
+        let Some(detector) = self.oscillation_detector else {
+            return;
+        };
+        let axes = [
+            ("Roll", telem.roll - telem.input_roll),
+            ("Pitch", telem.pitch - telem.input_pitch),
+        ];
+        for (i, (name, error)) in axes.into_iter().enumerate() {
+            let track = &mut self.oscillation_tracks[i];
+            let positive = error >= 0.0;
+            if track.positive.is_some_and(|p| p != positive) {
+                if track.peak >= detector.amplitude {
+                    track.crossings.push_back((telem.timestamp, telem.sample_index));
+                } else {
+                    // A small swing breaks the run; oscillation has to be sustained
+                    track.crossings.clear();
+                }
+                track.peak = 0.0;
+            }
+            track.positive = Some(positive);
+            track.peak = track.peak.max(error.abs());
+            while track
+                .crossings
+                .front()
+                .is_some_and(|&(t, _)| telem.timestamp.saturating_sub(t) > detector.window_ms)
+            {
+                track.crossings.pop_front();
+            }
+
+            if track.crossings.len() < OSCILLATION_MIN_CROSSINGS {
+                track.active = None;
+                continue;
+            }
+            if let Some(active) = track.active {
+                if let Some(episode) = self.oscillations.get_mut(active) {
+                    episode.end_timestamp = telem.timestamp;
+                    episode.end_index = telem.sample_index;
+                }
+                continue;
+            }
+
+            let (start, start_index) = track.crossings[0];
+            let span_secs = telem.timestamp.saturating_sub(start).max(1) as f32 / 1000.0;
+            let frequency = (track.crossings.len() - 1) as f32 / 2.0 / span_secs;
+            if self.oscillations.len() >= MAX_OSCILLATIONS {
+                self.oscillations.pop_front();
+                for track in &mut self.oscillation_tracks {
+                    track.active = track.active.and_then(|a| a.checked_sub(1));
+                }
+            }
+            self.oscillations.push_back(Oscillation {
+                axis: name,
+                start_timestamp: start,
+                start_index,
+                end_timestamp: telem.timestamp,
+                end_index: telem.sample_index,
+            });
+            self.oscillation_tracks[i].active = Some(self.oscillations.len() - 1);
+            self.push_log_level(
+                LogLevel::Warn,
+                format!(
+                    "{} oscillating at {:.1} s: ~{:.1} Hz, peaks above {:.1}°",
+                    name,
+                    start as f64 / 1000.0,
+                    frequency,
+                    detector.amplitude.to_degrees()
+                ),
+            );
+        }
+    }
+
+    pub fn record_raw(&mut self, packet: RawPacket) {
+        self.log.send(PendingLog::Raw(packet));
+    }
+
+    /// Counts a corrupt frame or unparseable line, copies it to the flight log if
+    /// enabled, and summarises errors in the log panel at most every few seconds
+    pub fn record_parse_error(&mut self, message: &str) {
+        self.parse_error_count += 1;
+        self.parse_errors_unreported += 1;
+        if self.recent_parse_errors.len() >= RECENT_PARSE_ERRORS {
+            self.recent_parse_errors.pop_front();
+        }
+        self.recent_parse_errors.push_back(message.to_string());
+
+        let due = self
+            .last_parse_report
+            .is_none_or(|t| t.elapsed().as_secs_f32() >= PARSE_ERROR_REPORT_SECS);
+        if due {
+            let count = std::mem::take(&mut self.parse_errors_unreported);
+            self.last_parse_report = Some(std::time::Instant::now());
+            self.push_log_level(
+                LogLevel::Warn,
+                if count == 1 {
+                    format!("Parse error: {}", message)
+                } else {
+                    format!("{} parse errors since last report (latest: {})", count, message)
+                },
+            );
+        } else {
+            // The summary above already reaches the flight log via push_log_level
+            self.log.send(PendingLog::FrameError {
+                clock_time: Local::now(),
+                message: message.to_string(),
+            });
+        }
+    }
+
+    /// Detects firmware timestamps going backwards and, if enabled, shifts later
+    /// samples so the plotted time stays monotonic
+    fn apply_time_base(&mut self, telem: &mut TelemetryData) {
+        let raw = telem.timestamp;
+        if let Some(prev_raw) = self.last_raw_timestamp
+            && raw < prev_raw
+        {
+            self.timestamp_rewinds += 1;
+            let prev_adjusted = prev_raw.wrapping_add(self.timestamp_offset);
+            if self.rebase_on_rewind {
+                self.timestamp_offset = prev_adjusted.saturating_add(1).saturating_sub(raw);
+            }
+            self.push_log_level(
+                LogLevel::Warn,
+                format!(
+                    "Telemetry timestamp went backwards ({} ms -> {} ms), drone rebooted?{}",
+                    prev_raw,
+                    raw,
+                    if self.rebase_on_rewind { " Time base continued." } else { "" }
+                ),
+            );
+        }
+        if self.detect_gaps
+            && let Some(prev_raw) = self.last_raw_timestamp
+        {
+            self.check_timestamp_gap(telem, prev_raw);
+        }
+        self.last_raw_timestamp = Some(raw);
+        telem.timestamp = raw.wrapping_add(self.timestamp_offset);
+    }
+
+    /// Flags repeated timestamps and intervals several times longer than usual,
+    /// which mean the firmware resent a frame or frames were lost on the way
+    fn check_timestamp_gap(&mut self, telem: &mut TelemetryData, prev_raw: u32) {
+        let raw = telem.timestamp;
+        if raw == prev_raw {
+            self.duplicate_timestamps += 1;
+            self.push_log_level(LogLevel::Warn, format!("Duplicate telemetry timestamp {} ms", raw));
+            return;
+        }
+        if raw < prev_raw {
+            // Reported as a rewind
+            return;
+        }
+
+        let interval_ms = raw - prev_raw;
+        let interval = interval_ms as f64;
+        let Some(typical) = self.typical_interval_ms else {
+            self.seed_gap_baseline(interval_ms);
+            return;
+        };
+        if interval > typical * GAP_INTERVAL_FACTOR {
+            telem.gap_before_ms = Some(interval_ms);
+            self.report_gap(interval_ms, typical);
+        } else {
+            self.typical_interval_ms = Some(typical + GAP_INTERVAL_SMOOTHING * (interval - typical));
+        }
+    }
+
+    /// Collects the first intervals and, once there are enough, takes their median as the
+    /// usual interval. Gaps among them are reported then; their samples aren't marked.
+    fn seed_gap_baseline(&mut self, interval_ms: u32) {
+        self.gap_baseline.push(interval_ms);
+        if self.gap_baseline.len() < GAP_BASELINE_INTERVALS {
+            return;
+        }
+        let mut sorted = std::mem::take(&mut self.gap_baseline);
+        sorted.sort_unstable();
+        let typical = sorted[sorted.len() / 2] as f64;
+        self.typical_interval_ms = Some(typical);
+        for interval_ms in sorted {
+            if interval_ms as f64 > typical * GAP_INTERVAL_FACTOR {
+                self.report_gap(interval_ms, typical);
+            }
+        }
+    }
+
+    fn report_gap(&mut self, interval_ms: u32, typical: f64) {
+        self.timestamp_gaps += 1;
+        self.push_log_level(
+            LogLevel::Warn,
+            format!(
+                "Telemetry gap: {} ms between samples (usually {:.0} ms), ~{} frame(s) lost",
+                interval_ms,
+                typical,
+                (interval_ms as f64 / typical).round() as u32 - 1
+            ),
+        );
     }
 
     pub fn push_log(&mut self, message: String) {
-        let log_msg = LogMessage {
-            _timestamp: self.start_time.elapsed().as_secs_f64(),
+        self.push_log_level(LogLevel::Info, message);
+    }
+
+    pub fn push_log_level(&mut self, level: LogLevel, message: String) {
+        self.log.send(PendingLog::Message {
             clock_time: Local::now(),
+            level,
             message,
-        };
+        });
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_buffer::LogBuffer;
+
+    fn buffer_with_samples(count: u32) -> DataBuffer {
+        let mut buffer = DataBuffer::new(LogBuffer::new().sender());
+        for i in 0..count {
+            let mut packet = TelemetryPacket::zeroed();
+            packet.timestamp_ms = i * 10;
+            packet.roll = i as f32;
+            buffer.push(TelemetryData::from(&packet));
+        }
+        buffer
+    }
+
+    fn roll_points(buffer: &DataBuffer) -> Vec<[f64; 2]> {
+        buffer.visible(None).map(|d| [d.timestamp as f64, d.roll as f64]).collect()
+    }
 
-        if self.logs.len() >= MAX_LOG_MESSAGES {
-            self.logs.pop_front();
+    /// Base payload: timestamp 1234 ms, then the 27 floats in wire order set to 1.0, 2.0, …
+    fn known_payload() -> Vec<u8> {
+        let mut payload = 1234u32.to_le_bytes().to_vec();
+        for i in 1..=27 {
+            payload.extend_from_slice(&(i as f32).to_le_bytes());
         }
-        self.logs.push_back(log_msg);
+        payload
+    }
+
+    fn gps_trailer(lat: f64, lon: f64, sats: u8) -> Vec<u8> {
+        let mut trailer = lat.to_le_bytes().to_vec();
+        trailer.extend_from_slice(&lon.to_le_bytes());
+        trailer.push(sats);
+        trailer
+    }
+
+    #[test]
+    fn decodes_base_payload_in_wire_order() {
+        let payload = known_payload();
+        assert_eq!(payload.len(), TelemetryPacket::LEN);
+        let d = TelemetryData::from_payload(&payload).expect("payload decodes");
+        assert_eq!(d.timestamp, 1234);
+        #[rustfmt::skip]
+        let decoded = [
+            d.roll, d.pitch, d.yaw,
+            d.roll_p, d.roll_i, d.roll_d,
+            d.pitch_p, d.pitch_i, d.pitch_d,
+            d.yaw_p, d.yaw_i, d.yaw_d,
+            d.gyro_x, d.gyro_y, d.gyro_z,
+            d.vel_x, d.vel_y, d.vel_z,
+            d.height,
+            d.motor1, d.motor2, d.motor3, d.motor4,
+            d.input_throttle, d.input_roll, d.input_pitch, d.input_yaw,
+        ];
+        let expected: Vec<f32> = (1..=27).map(|i| i as f32).collect();
+        assert_eq!(decoded.to_vec(), expected);
+        assert_eq!((d.lat, d.lon, d.sats), (None, None, 0));
+    }
+
+    #[test]
+    fn decodes_gps_trailer() {
+        let mut payload = known_payload();
+        payload.extend(gps_trailer(47.3977419, 8.5455938, 9));
+        assert_eq!(payload.len(), TelemetryPacket::LEN_WITH_GPS);
+        let d = TelemetryData::from_payload(&payload).expect("payload decodes");
+        assert_eq!(d.roll, 1.0);
+        assert_eq!((d.lat, d.lon, d.sats), (Some(47.3977419), Some(8.5455938), 9));
+    }
+
+    #[test]
+    fn gps_trailer_without_fix_has_no_position() {
+        let mut payload = known_payload();
+        payload.extend(gps_trailer(0.0, 0.0, 0));
+        let d = TelemetryData::from_payload(&payload).expect("payload decodes");
+        assert_eq!((d.lat, d.lon, d.sats), (None, None, 0));
     }
 
+    #[test]
+    fn rejects_payload_of_other_lengths() {
+        let payload = known_payload();
+        assert!(TelemetryData::from_payload(&payload[..TelemetryPacket::LEN - 1]).is_none());
+        let mut long = payload.clone();
+        long.extend_from_slice(&[0; 3]);
+        assert!(TelemetryData::from_payload(&long).is_none());
+    }
+
+    #[test]
+    fn cached_series_hit_shares_points() {
+        let buffer = buffer_with_samples(1000);
+        let first = buffer.cached_series("roll", 0, || roll_points(&buffer));
+        assert_eq!(first.len(), 1000);
+
+        let second = buffer.cached_series("roll", 0, || unreachable!("series was cached"));
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn cached_series_picks_up_new_samples_after_rebuild_interval() {
+        let mut buffer = buffer_with_samples(10);
+        buffer.cached_series("roll", 0, || roll_points(&buffer));
+        buffer.push(TelemetryData::from(&TelemetryPacket::zeroed()));
+
+        assert_eq!(buffer.cached_series("roll", 0, || roll_points(&buffer)).len(), 10);
+        std::thread::sleep(SERIES_REBUILD_INTERVAL);
+        assert_eq!(buffer.cached_series("roll", 0, || roll_points(&buffer)).len(), 11);
+        assert!(buffer.cached_series("roll", 1, Vec::new).is_empty());
+    }
+
+    fn gap_buffer(timestamps: &[u32]) -> DataBuffer {
+        let mut buffer = DataBuffer::new(LogBuffer::new().sender());
+        buffer.detect_gaps = true;
+        for &timestamp in timestamps {
+            let mut sample = TelemetryData::from(&TelemetryPacket::zeroed());
+            sample.timestamp = timestamp;
+            buffer.push(sample);
+        }
+        buffer
+    }
+
+    fn gap_marks(buffer: &DataBuffer) -> Vec<(u32, u32)> {
+        buffer
+            .data
+            .iter()
+            .filter_map(|d| d.gap_before_ms.map(|gap| (d.timestamp, gap)))
+            .collect()
+    }
+
+    #[test]
+    fn gap_mid_stream_is_flagged() {
+        let mut timestamps: Vec<u32> = (0..20).map(|i| i * 10).collect();
+        timestamps.extend((0..20).map(|i| 300 + i * 10));
+        let buffer = gap_buffer(&timestamps);
+        assert_eq!(buffer.timestamp_gaps, 1);
+        assert_eq!(gap_marks(&buffer), vec![(300, 110)]);
+    }
+
+    #[test]
+    fn gap_at_start_does_not_become_the_baseline() {
+        let mut timestamps = vec![0];
+        timestamps.extend((0..30).map(|i| 500 + i * 10));
+        timestamps.extend((0..10).map(|i| 1000 + i * 10));
+        let buffer = gap_buffer(&timestamps);
+        // The 500 ms start gap is reported once the baseline settles at 10 ms,
+        // and the later 210 ms gap is still caught
+        assert_eq!(buffer.timestamp_gaps, 2);
+        assert_eq!(gap_marks(&buffer), vec![(1000, 210)]);
+    }
 }
@@ -0,0 +1,127 @@
+// Byte-stream links the UART thread can read telemetry from: a local serial
+// port, or a TCP connection to a bridge that forwards the radio's bytes.
+
+use serde::{Deserialize, Serialize};
+use serialport::SerialPort;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::config::SERIAL_TIMEOUT_MS;
+
+const TCP_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Raw link under the frame parser. `read` should time out after a short while
+/// so the UART thread can service outgoing commands.
+pub trait Transport: Send {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()>;
+}
+
+impl Transport for Box<dyn SerialPort> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Read::read(self, buf)
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        Write::write_all(self, data)
+    }
+}
+
+impl Transport for TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match Read::read(self, buf) {
+            // A zero-length read on a socket means the peer hung up
+            Ok(0) if !buf.is_empty() => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "connection closed by peer",
+            )),
+            result => result,
+        }
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        Write::write_all(self, data)
+    }
+}
+
+/// Which kind of link the connection panel opens
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum TransportKind {
+    #[default]
+    Serial,
+    Tcp,
+}
+
+impl TransportKind {
+    pub const ALL: [TransportKind; 2] = [TransportKind::Serial, TransportKind::Tcp];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TransportKind::Serial => "Serial",
+            TransportKind::Tcp => "TCP",
+        }
+    }
+}
+
+/// Everything needed to open a link; compared to spot settings that changed since connecting
+#[derive(Clone, Debug, PartialEq)]
+pub enum Endpoint {
+    Serial { port_path: String, baud_rate: u32 },
+    Tcp { address: String },
+}
+
+impl Endpoint {
+    pub fn describe(&self) -> String {
+        match self {
+            Endpoint::Serial { port_path, baud_rate } => format!("{} @ {} baud", port_path, baud_rate),
+            Endpoint::Tcp { address } => format!("tcp://{}", address),
+        }
+    }
+
+    pub fn open(&self) -> Result<Box<dyn Transport>, String> {
+        match self {
+            Endpoint::Serial { port_path, baud_rate } => serialport::new(port_path, *baud_rate)
+                .timeout(Duration::from_millis(SERIAL_TIMEOUT_MS))
+                .open()
+                .map(|port| Box::new(port) as Box<dyn Transport>)
+                .map_err(|e| {
+                    let mut msg = format!("failed to open port '{}': {}", port_path, e);
+                    if let Some(hint) = open_error_hint(&e) {
+                        msg.push_str(" (");
+                        msg.push_str(hint);
+                        msg.push(')');
+                    }
+                    msg
+                }),
+            Endpoint::Tcp { address } => open_tcp(address)
+                .map(|stream| Box::new(stream) as Box<dyn Transport>)
+                .map_err(|e| format!("failed to connect to '{}': {}", address, e)),
+        }
+    }
+}
+
+fn open_tcp(address: &str) -> io::Result<TcpStream> {
+    let addr = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "address did not resolve"))?;
+    let stream = TcpStream::connect_timeout(&addr, TCP_CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(Duration::from_millis(SERIAL_TIMEOUT_MS)))?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+/// Suggests a fix for common port-open failures
+fn open_error_hint(error: &serialport::Error) -> Option<&'static str> {
+    match error.kind() {
+        serialport::ErrorKind::Io(io::ErrorKind::PermissionDenied) => Some(if cfg!(target_os = "linux") {
+            "permission denied: add your user to the dialout group with \
+             `sudo usermod -aG dialout $USER`, then log out and back in"
+        } else {
+            "permission denied: check that no other program has the port open"
+        }),
+        serialport::ErrorKind::NoDevice => Some("device not found: check the cable and port name"),
+        _ => None,
+    }
+}
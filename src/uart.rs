@@ -1,58 +1,136 @@
-use bytemuck;
-use serialport::SerialPort;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
 use std::time::Duration;
 
-use crate::config::{BAUD_RATE, SERIAL_TIMEOUT_MS};
-use crate::parser::{parse_ack, parse_err, parse_log};
-use crate::telemetry::{DataBuffer, TelemetryPacket};
+use crate::config::MAX_SEND_BYTES;
+use crate::parser::{parse_ack, parse_calibration, parse_err, parse_esc, parse_log, parse_version};
+use crate::protocol::PROTOCOL_VERSION;
+use crate::raw_capture::RawCapture;
+use crate::status::StatusSender;
+use crate::telemetry::{DataBuffer, LogLevel, RawPacket, TelemetryData, TelemetryPacket};
+use crate::transport::{Endpoint, Transport};
+
+/// Link state reported by the UART thread, shown as the status dot in the connection panel
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ConnectionState {
+    #[default]
+    Disconnected,
+    Opening,
+    Connected,
+    Error(String),
+}
+
+impl ConnectionState {
+    pub fn describe(&self) -> String {
+        match self {
+            ConnectionState::Disconnected => "Disconnected".to_string(),
+            ConnectionState::Opening => "Opening port…".to_string(),
+            ConnectionState::Connected => "Port open".to_string(),
+            ConnectionState::Error(e) => format!("Error: {}", e),
+        }
+    }
+}
+
+fn set_state(shared: &Arc<Mutex<ConnectionState>>, new_state: ConnectionState) {
+    if let Ok(mut state) = shared.lock()
+        && *state != new_state
+    {
+        *state = new_state;
+    }
+}
 
 pub enum UartCommand {
     Send { data: Vec<u8> },
+    /// Start hex-dumping every received chunk to a new file in `dir`
+    StartCapture { dir: PathBuf },
+    StopCapture,
     Disconnect,
 }
 
 const BT_SYNC: u8 = 0xA5;
 const BT_TELEM: u8 = 0x10;
 
+/// Handle to a running UART thread
+pub struct UartThread {
+    pub sender: mpsc::Sender<UartCommand>,
+    /// Cleared when the thread exits, whether asked to or because the link failed
+    pub alive: Arc<AtomicBool>,
+}
+
+/// Spawns the UART thread, which opens `endpoint` and then reads from it. Opening can
+/// block (DNS, TCP connect), so its outcome is reported through `connection_state`.
 pub fn start_uart_thread(
-    port_path: String,
+    endpoint: Endpoint,
     data_buffer: Arc<Mutex<DataBuffer>>,
-) -> Result<mpsc::Sender<UartCommand>, String> {
-    let port = serialport::new(&port_path, BAUD_RATE)
-        .timeout(Duration::from_millis(SERIAL_TIMEOUT_MS))
-        .open()
-        .map_err(|e| format!("failed to open port '{}': {}", port_path, e))?;
-
+    connection_state: Arc<Mutex<ConnectionState>>,
+    status: StatusSender,
+) -> UartThread {
+    set_state(&connection_state, ConnectionState::Opening);
     let (tx, rx) = mpsc::channel();
+    let alive = Arc::new(AtomicBool::new(true));
+    let thread_alive = Arc::clone(&alive);
     thread::spawn(move || {
-        uart_loop(port, data_buffer, rx);
+        match endpoint.open() {
+            Ok(port) => {
+                set_state(&connection_state, ConnectionState::Connected);
+                status.info(format!("Opened {}", endpoint.describe()));
+                uart_loop(port, data_buffer, connection_state, rx, status);
+            }
+            Err(msg) => {
+                status.error(format!("Connection failed: {}", msg));
+                set_state(&connection_state, ConnectionState::Error(msg));
+            }
+        }
+        thread_alive.store(false, Ordering::Release);
     });
-
-    println!("Serial port {} opened at {} baud", port_path, BAUD_RATE);
-    Ok(tx)
+    UartThread { sender: tx, alive }
 }
 
 fn uart_loop(
-    mut port: Box<dyn SerialPort>,
+    mut port: Box<dyn Transport>,
     data_buffer: Arc<Mutex<DataBuffer>>,
+    connection_state: Arc<Mutex<ConnectionState>>,
     rx: mpsc::Receiver<UartCommand>,
+    status: StatusSender,
 ) {
     let mut serial_buf = vec![0u8; 256];
     let mut parser = RxParser::new();
+    let mut capture: Option<RawCapture> = None;
+    // Read errors repeat every retry, so only the first of a run is reported
+    let mut read_failing = false;
 
     loop {
         if let Ok(cmd) = rx.try_recv() {
             match cmd {
                 UartCommand::Disconnect => {
-                    println!("Disconnecting from serial port");
+                    status.info("Disconnecting");
                     drop(port);
+                    set_state(&connection_state, ConnectionState::Disconnected);
                     break;
                 }
                 UartCommand::Send { data } => {
-                    if let Err(e) = port.write_all(&data) {
-                        eprintln!("Failed to send binary frame: {}", e);
+                    if data.len() > MAX_SEND_BYTES {
+                        status.error(format!(
+                            "Refused to send {} bytes, over the {}-byte limit",
+                            data.len(),
+                            MAX_SEND_BYTES
+                        ));
+                    } else if let Err(e) = port.write_all(&data) {
+                        status.error(format!("Failed to send binary frame: {}", e));
+                    }
+                }
+                UartCommand::StartCapture { dir } => match RawCapture::open(&dir) {
+                    Ok(c) => {
+                        status.info(format!("Raw capture to {}", c.path.display()));
+                        capture = Some(c);
+                    }
+                    Err(e) => status.warn(format!("Raw capture failed: {}", e)),
+                },
+                UartCommand::StopCapture => {
+                    if let Some(c) = capture.take() {
+                        status.info(format!("Raw capture saved to {}", c.path.display()));
                     }
                 }
             }
@@ -60,14 +138,32 @@ fn uart_loop(
 
         match port.read(&mut serial_buf) {
             Ok(n) if n > 0 => {
+                read_failing = false;
+                set_state(&connection_state, ConnectionState::Connected);
+                if let Some(c) = &mut capture {
+                    c.write_chunk(&serial_buf[..n]);
+                }
                 parser.feed(&serial_buf[..n], &data_buffer);
             }
             Ok(_) => {}
-            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {}
-            Err(_) => thread::sleep(Duration::from_millis(100)),
+            Err(ref e)
+                if matches!(e.kind(), std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                status.error(e.to_string());
+                set_state(&connection_state, ConnectionState::Error(e.to_string()));
+                break;
+            }
+            Err(e) => {
+                if !read_failing {
+                    status.error(format!("Read failed: {}", e));
+                    read_failing = true;
+                }
+                set_state(&connection_state, ConnectionState::Error(format!("read failed: {}", e)));
+                thread::sleep(Duration::from_millis(100));
+            }
         }
     }
-    println!("UART thread exited");
+    status.info("Thread exited");
 }
 
 /// Parses a mixed binary-frame + text-line byte stream.
@@ -76,7 +172,8 @@ fn uart_loop(
 /// Text lines are UTF-8 terminated by '\n'.
 struct RxParser {
     state: ParseState,
-    line_buf: String,
+    /// Raw bytes of the current text line, decoded only once the line is complete
+    line_buf: Vec<u8>,
 }
 
 enum ParseState {
@@ -91,7 +188,7 @@ impl RxParser {
     fn new() -> Self {
         Self {
             state: ParseState::Text,
-            line_buf: String::new(),
+            line_buf: Vec::new(),
         }
     }
 
@@ -102,10 +199,10 @@ impl RxParser {
                     if byte == BT_SYNC {
                         self.state = ParseState::FrameType;
                     } else {
-                        self.line_buf.push(byte as char);
+                        self.line_buf.push(byte);
                         if byte == b'\n' {
                             let line = std::mem::take(&mut self.line_buf);
-                            let trimmed = line.trim().to_string();
+                            let trimmed = String::from_utf8_lossy(&line).trim().to_string();
                             if !trimmed.is_empty() {
                                 process_line(&trimmed, data_buffer);
                             }
@@ -118,14 +215,14 @@ impl RxParser {
                         self.state = ParseState::FrameLen(byte);
                     } else {
                         self.state = ParseState::Text;
+                        record_parse_error(data_buffer, format!("Unknown frame type 0x{:02X}", byte));
                     }
                 }
 
                 ParseState::FrameLen(pkt_type) => {
                     let pkt_type = *pkt_type;
                     let len = byte as usize;
-                    let expected_len = std::mem::size_of::<TelemetryPacket>();
-                    if len == expected_len {
+                    if len == TelemetryPacket::LEN || len == TelemetryPacket::LEN_WITH_GPS {
                         self.state = ParseState::FramePayload {
                             pkt_type,
                             expected: len,
@@ -133,6 +230,16 @@ impl RxParser {
                         };
                     } else {
                         self.state = ParseState::Text; // wrong length, discard
+                        record_parse_error(
+                            data_buffer,
+                            format!(
+                                "Frame 0x{:02X} has length {} (expected {} or {})",
+                                pkt_type,
+                                len,
+                                TelemetryPacket::LEN,
+                                TelemetryPacket::LEN_WITH_GPS
+                            ),
+                        );
                     }
                 }
 
@@ -156,8 +263,19 @@ impl RxParser {
                     for &b in &payload {
                         crc = crc8_dvb_s2(crc, b);
                     }
+                    if let Ok(mut buf) = data_buffer.lock() {
+                        buf.record_raw(RawPacket::frame(pkt_type, &payload, crc == byte));
+                    }
                     if crc == byte {
                         process_frame(pkt_type, &payload, data_buffer);
+                    } else {
+                        record_parse_error(
+                            data_buffer,
+                            format!(
+                                "Frame 0x{:02X} CRC mismatch (got 0x{:02X}, expected 0x{:02X})",
+                                pkt_type, byte, crc
+                            ),
+                        );
                     }
                 }
             }
@@ -167,9 +285,9 @@ impl RxParser {
 
 fn process_frame(pkt_type: u8, payload: &[u8], data_buffer: &Arc<Mutex<DataBuffer>>) {
     if pkt_type == BT_TELEM {
-        if let Ok(packet) = bytemuck::try_from_bytes::<TelemetryPacket>(payload) {
+        if let Some(telem) = TelemetryData::from_payload(payload) {
             if let Ok(mut buf) = data_buffer.lock() {
-                buf.push(packet.into());
+                buf.push(telem);
             }
         }
     }
@@ -179,13 +297,60 @@ fn process_line(line: &str, data_buffer: &Arc<Mutex<DataBuffer>>) {
     let Ok(mut buf) = data_buffer.lock() else {
         return;
     };
+    buf.record_raw(RawPacket::line(line));
 
     if let Some(ack) = parse_ack(line) {
-        buf.push_log(format!("ACK: {}", ack));
-    } else if let Some(log_msg) = parse_log(line) {
-        buf.push_log(log_msg);
+        match buf.commands.acknowledge(ack) {
+            Some(command) => buf.push_log(format!(
+                "ACK: {} (#{}, {} ms)",
+                ack,
+                command.seq,
+                command.sent_at.elapsed().as_millis()
+            )),
+            None => buf.push_log(format!("ACK: {}", ack)),
+        }
+        if let Some(batch) = buf.commands.take_finished_batch()
+            && !batch.failed
+        {
+            buf.push_log(format!(
+                "Send All applied: {} commands ACKed in {} ms",
+                batch.expected,
+                batch.started.elapsed().as_millis()
+            ));
+        }
+    } else if let Some((level, log_msg)) = parse_log(line) {
+        let calibration = parse_calibration(&log_msg).and_then(|event| buf.commands.update_calibration(event));
+        buf.push_log_level(level, log_msg);
+        if let Some(elapsed) = calibration {
+            buf.push_log(format!("Calibration finished in {:.1}s", elapsed.as_secs_f32()));
+        }
+    } else if let Some(esc) = parse_esc(line) {
+        buf.push_esc(esc);
+    } else if let Some(version) = parse_version(line) {
+        if version.is_incompatible() {
+            buf.push_log_level(
+                LogLevel::Warn,
+                format!(
+                    "Firmware {} speaks protocol v{}, this GUI v{}; commands will not be sent",
+                    version.text,
+                    version.protocol().unwrap_or_default(),
+                    PROTOCOL_VERSION
+                ),
+            );
+        } else {
+            buf.push_log(format!("Firmware version {}", version.text));
+        }
+        buf.firmware_version = Some(version);
     } else if let Some(err) = parse_err(line) {
-        buf.push_log(format!("ERR: {}", err));
+        buf.push_log_level(LogLevel::Error, format!("ERR: {}", err));
+    } else {
+        buf.record_parse_error(&format!("Unrecognised line: {:?}", line));
+    }
+}
+
+fn record_parse_error(data_buffer: &Arc<Mutex<DataBuffer>>, message: String) {
+    if let Ok(mut buf) = data_buffer.lock() {
+        buf.record_parse_error(&message);
     }
 }
 
@@ -196,3 +361,93 @@ fn crc8_dvb_s2(mut crc: u8, byte: u8) -> u8 {
     }
     crc
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_buffer::LogBuffer;
+
+    fn buffers() -> (Arc<Mutex<DataBuffer>>, Arc<Mutex<LogBuffer>>) {
+        let log = Arc::new(Mutex::new(LogBuffer::new()));
+        let data = DataBuffer::new(log.lock().unwrap().sender());
+        (Arc::new(Mutex::new(data)), log)
+    }
+
+    fn logged(log: &Arc<Mutex<LogBuffer>>) -> Vec<String> {
+        let mut log = log.lock().unwrap();
+        log.drain_pending();
+        log.logs.iter().map(|m| m.message.clone()).collect()
+    }
+
+    #[test]
+    fn line_split_inside_multibyte_character() {
+        let (data, log) = buffers();
+        let mut parser = RxParser::new();
+        let line = "LOG:Höhe 2 m\n".as_bytes();
+        // 'ö' is two bytes; cut between them
+        let split = line.iter().position(|&b| b == 0xC3).unwrap() + 1;
+        parser.feed(&line[..split], &data);
+        assert!(logged(&log).is_empty());
+        parser.feed(&line[split..], &data);
+        assert_eq!(logged(&log), vec!["Höhe 2 m".to_string()]);
+        assert_eq!(data.lock().unwrap().parse_error_count, 0);
+    }
+
+    fn telemetry_frame() -> Vec<u8> {
+        let payload = vec![0u8; TelemetryPacket::LEN];
+        let mut crc = crc8_dvb_s2(0, BT_TELEM);
+        crc = crc8_dvb_s2(crc, payload.len() as u8);
+        for &b in &payload {
+            crc = crc8_dvb_s2(crc, b);
+        }
+        let mut frame = vec![BT_SYNC, BT_TELEM, payload.len() as u8];
+        frame.extend_from_slice(&payload);
+        frame.push(crc);
+        frame
+    }
+
+    fn parse_errors_after(bytes: &[u8]) -> (usize, usize) {
+        let (data, _log) = buffers();
+        RxParser::new().feed(bytes, &data);
+        let buf = data.lock().unwrap();
+        (buf.parse_error_count, buf.data.len())
+    }
+
+    #[test]
+    fn valid_frame_is_not_an_error() {
+        assert_eq!(parse_errors_after(&telemetry_frame()), (0, 1));
+    }
+
+    #[test]
+    fn truncated_frame_is_counted() {
+        // The cut-off frame swallows the start of the next one, so its CRC can't match
+        let mut bytes = telemetry_frame()[..10].to_vec();
+        bytes.extend(telemetry_frame());
+        let (errors, samples) = parse_errors_after(&bytes);
+        assert!(errors >= 1);
+        assert_eq!(samples, 0);
+    }
+
+    #[test]
+    fn bad_length_is_counted() {
+        assert_eq!(parse_errors_after(&[BT_SYNC, BT_TELEM, 5, 1, 2, 3, 4, 5]).0, 1);
+    }
+
+    #[test]
+    fn unknown_frame_type_is_counted() {
+        assert_eq!(parse_errors_after(&[BT_SYNC, 0x7F]).0, 1);
+    }
+
+    #[test]
+    fn crc_mismatch_is_counted() {
+        let mut frame = telemetry_frame();
+        *frame.last_mut().unwrap() ^= 0xFF;
+        assert_eq!(parse_errors_after(&frame), (1, 0));
+    }
+
+    #[test]
+    fn unrecognised_line_is_counted() {
+        assert_eq!(parse_errors_after(b"+RCV=1,2\n").0, 1);
+        assert_eq!(parse_errors_after(b"garbage\n\n").0, 1);
+    }
+}
@@ -1,9 +1,12 @@
 pub mod panels;
+pub mod widgets;
 pub mod windows;
 
 use crate::app::{AppState, CommandQueue};
 use crate::drone_scene::{Drone, DroneOrientation, ViewportImage};
-use crate::persistence::PersistentSettings;
+use crate::export;
+use crate::persistence::{MainSection, PersistentSettings, Theme};
+use crate::telemetry::{BufferFullPolicy, LogLevel};
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, egui};
 
@@ -20,20 +23,33 @@ pub fn ui_system(
     if state.viewport_texture_id.is_none() {
         let egui_texture_id = contexts.add_image(viewport_image.handle.clone());
         state.viewport_texture_id = Some(egui_texture_id);
-        println!(
+        info!(
             "Registered viewport texture with egui: {:?}",
             egui_texture_id
         );
     }
 
+    if let Ok(mut log) = state.log_buffer.lock() {
+        log.drain_pending();
+        state.status.drain_into(&mut log);
+    }
+
     // Update drone orientation from telemetry
     update_drone_orientation(&state, &mut drone_query);
 
+    // Push settings that affect data ingestion into the shared buffer
+    state.sync_buffer_settings(&persistent_settings);
+
     let ctx = contexts.ctx_mut();
     ctx.request_repaint();
+    apply_theme(ctx, persistent_settings.theme);
+    widgets::apply_touch_style(ctx, persistent_settings.touch_mode);
 
     // Top Panel - Connection controls
-    render_top_panel(ctx, &mut state);
+    render_top_panel(ctx, &mut state, &mut persistent_settings);
+
+    // Pinned strip with the most important live values
+    render_telemetry_strip(ctx, &state);
 
     // Central Panel - Main content
     render_central_panel(
@@ -45,6 +61,16 @@ pub fn ui_system(
 
     // PID Tuning Window
     windows::render_pid_tuning_window(ctx, &mut state, &command_queue, &mut persistent_settings);
+    windows::render_config_summary_window(ctx, &mut state, &command_queue, &persistent_settings);
+    windows::render_reset_confirmation_window(ctx, &mut state, &mut persistent_settings);
+}
+
+/// Switches egui's visuals when the saved theme differs from the active one
+fn apply_theme(ctx: &egui::Context, theme: Theme) {
+    let preference = theme.preference();
+    if ctx.options(|o| o.theme_preference) != preference {
+        ctx.set_theme(preference);
+    }
 }
 
 /// Updates the drone orientation in the 3D scene from telemetry data
@@ -52,19 +78,32 @@ fn update_drone_orientation(
     state: &AppState,
     drone_query: &mut Query<&mut DroneOrientation, With<Drone>>,
 ) {
-    if let Ok(buffer) = state.data_buffer.lock()
-        && let Some(latest) = buffer.data.back()
-    {
-        for mut orientation in drone_query.iter_mut() {
-            orientation.roll = latest.roll;
-            orientation.pitch = latest.pitch;
-            orientation.yaw = latest.yaw;
+    let Ok(buffer) = state.data_buffer.lock() else {
+        return;
+    };
+    let staleness = match buffer.last_sample_at {
+        Some(t) => ((t.elapsed().as_secs_f32() - STALE_FADE_START_SECS) / STALE_FADE_SECS).clamp(0.0, 1.0),
+        None => 1.0,
+    };
+    for mut orientation in drone_query.iter_mut() {
+        if let Some(latest) = buffer.data.back() {
+            orientation.apply_sample(latest);
         }
+        orientation.staleness = staleness;
     }
 }
 
+/// Telemetry older than this starts fading the 3D model
+const STALE_FADE_START_SECS: f32 = 0.5;
+/// Time over which the model fades from live colors to gray
+const STALE_FADE_SECS: f32 = 2.0;
+
 /// Renders the top connection panel
-fn render_top_panel(ctx: &egui::Context, state: &mut AppState) {
+fn render_top_panel(
+    ctx: &egui::Context,
+    state: &mut AppState,
+    persistent_settings: &mut PersistentSettings,
+) {
     egui::TopBottomPanel::top("top_panel")
         .frame(egui::Frame {
             inner_margin: egui::Margin::same(8.0),
@@ -72,7 +111,73 @@ fn render_top_panel(ctx: &egui::Context, state: &mut AppState) {
             ..Default::default()
         })
         .show(ctx, |ui| {
-            panels::render_connection_panel(ui, state);
+            panels::render_connection_panel(ui, state, persistent_settings);
+        });
+}
+
+/// Renders a single always-visible row of safety-critical values
+fn render_telemetry_strip(ctx: &egui::Context, state: &AppState) {
+    egui::TopBottomPanel::top("telemetry_strip")
+        .frame(egui::Frame {
+            inner_margin: egui::Margin::symmetric(8.0, 4.0),
+            fill: ctx.style().visuals.panel_fill,
+            ..Default::default()
+        })
+        .show(ctx, |ui| {
+            let buffer = state.data_buffer.lock().unwrap();
+            let warn = ui.visuals().warn_fg_color;
+            let ok = if ui.visuals().dark_mode {
+                egui::Color32::GREEN
+            } else {
+                egui::Color32::from_rgb(0, 140, 0)
+            };
+            ui.horizontal(|ui| {
+                let rate = buffer.telemetry_rate_hz();
+                let age = buffer.last_sample_at.map(|t| t.elapsed().as_secs_f32());
+                let (link_text, link_color) = match age {
+                    Some(age) if age < 1.0 => (format!("Link: {} Hz", rate), ok),
+                    Some(age) => (format!("Link: stale {:.1}s", age), warn),
+                    None => ("Link: no data".to_string(), egui::Color32::GRAY),
+                };
+                ui.label(egui::RichText::new(link_text).monospace().color(link_color));
+                if buffer.parse_error_count > 0 {
+                    ui.label(
+                        egui::RichText::new(format!("Parse errors: {}", buffer.parse_error_count))
+                            .monospace()
+                            .color(warn),
+                    );
+                }
+
+                if let Some(latest) = buffer.data.back() {
+                    ui.separator();
+                    ui.monospace(format!("Height: {:.2} m", latest.height));
+                    ui.separator();
+                    ui.monospace(format!("Throttle: {:.0}%", latest.input_throttle * 100.0));
+                    ui.separator();
+                    let unit = state.angle_unit;
+                    ui.monospace(format!(
+                        "R/P/Y: {} {} {}",
+                        unit.format(latest.roll),
+                        unit.format(latest.pitch),
+                        unit.format(latest.yaw)
+                    ));
+                    if buffer.is_deviation_alarm_active() {
+                        ui.separator();
+                        ui.label(
+                            egui::RichText::new("Not tracking setpoint")
+                                .monospace()
+                                .color(warn),
+                        );
+                    }
+                    ui.separator();
+                    let vibration = format!("Vib: {:.1}", buffer.vibration);
+                    if buffer.is_vibration_high() {
+                        ui.label(egui::RichText::new(vibration).monospace().color(warn));
+                    } else {
+                        ui.monospace(vibration);
+                    }
+                }
+            });
         });
 }
 
@@ -96,53 +201,256 @@ fn render_central_panel(
                     // Horizontal layout: View | Commands | Log
                     render_main_sections(ui, state, command_queue, persistent_settings);
 
-                    // Clear plots button
-                    if ui.button("clear plots").clicked() {
-                        state.data_buffer.lock().unwrap().clear_data();
+                    ui.horizontal(|ui| {
+                        // Clear plots button
+                        if ui.button("clear plots").clicked() {
+                            state.data_buffer.lock().unwrap().clear_data();
+                            state.plot_cursor_x = None;
+                        }
+
+                        if ui.button("export CSV").clicked() {
+                            export_telemetry(state, persistent_settings);
+                        }
+
+                        if ui
+                            .button("bug report")
+                            .on_hover_text("Save settings, logs, diagnostics and recent telemetry to one JSON file")
+                            .clicked()
+                        {
+                            export_bug_report(state, persistent_settings);
+                        }
+
+                        ui.separator();
+                        render_marker_input(ui, state);
+
+                        ui.separator();
+                        panels::render_plot_window_selector(ui, state, persistent_settings);
+
+                        ui.separator();
+                        render_buffer_policy(ui, state, persistent_settings);
+                    });
+
+                    if persistent_settings.plots_collapsed {
+                        return;
                     }
 
                     // Attitude and PID plots
-                    panels::render_attitude_plot(ui, state);
-                    panels::render_pid_plot(ui, state);
-                    panels::render_gyro_plot(ui, state);
+                    panels::render_attitude_plot(ui, state, persistent_settings);
+                    panels::render_rate_plot(ui, state, persistent_settings);
+                    panels::render_fft_plot(ui, state, persistent_settings);
+                    panels::render_pid_plot(ui, state, persistent_settings);
+                    panels::render_gyro_plot(ui, state, persistent_settings);
                     panels::render_velocity_plot(ui, state);
+                    panels::render_position_plot(ui, state);
                     panels::render_motor_plot(ui, state);
+                    panels::render_esc_plot(ui, state);
+                    panels::render_custom_plot(ui, state);
+                    panels::finish_plot_cursor(state);
                 });
         });
 }
 
-/// Renders the three main sections (viewport, commands, logs)
+/// Selector for what happens when the telemetry buffer is full, plus its status
+fn render_buffer_policy(
+    ui: &mut egui::Ui,
+    state: &AppState,
+    persistent_settings: &mut PersistentSettings,
+) {
+    ui.label("When buffer full:");
+    egui::ComboBox::from_id_salt("buffer_full_policy")
+        .selected_text(persistent_settings.buffer_full_policy.label())
+        .show_ui(ui, |ui| {
+            for policy in BufferFullPolicy::ALL {
+                ui.selectable_value(&mut persistent_settings.buffer_full_policy, policy, policy.label());
+            }
+        });
+
+    ui.checkbox(
+        &mut persistent_settings.rebase_on_timestamp_rewind,
+        "Continue time on rewind",
+    )
+    .on_hover_text("Keep plotted time monotonic when the drone's timestamp jumps backwards (e.g. after a reboot)");
+    ui.checkbox(&mut persistent_settings.detect_timestamp_gaps, "Detect gaps")
+        .on_hover_text("Warn about lost or repeated frames using the firmware timestamp; gaps are marked on the attitude plot");
+
+    let buffer = state.data_buffer.lock().unwrap();
+    if buffer.timestamp_rewinds > 0 {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!("{} timestamp rewind(s)", buffer.timestamp_rewinds),
+        );
+    }
+    if buffer.timestamp_gaps > 0 || buffer.duplicate_timestamps > 0 {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!(
+                "{} gap(s), {} duplicate(s)",
+                buffer.timestamp_gaps, buffer.duplicate_timestamps
+            ),
+        );
+    }
+    if buffer.is_recording_stopped() {
+        ui.colored_label(ui.visuals().warn_fg_color, "Buffer full, recording stopped");
+    } else if buffer.downsample_stride > 1 {
+        ui.label(format!("Keeping 1 in {} samples", buffer.downsample_stride));
+    }
+}
+
+/// Text box and button dropping a labeled marker on every plot at the latest sample
+fn render_marker_input(ui: &mut egui::Ui, state: &mut AppState) {
+    let edit = ui.add(
+        egui::TextEdit::singleline(&mut state.marker_text)
+            .hint_text("note, e.g. windy now")
+            .desired_width(140.0),
+    );
+    let entered = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+    let label = state.marker_text.trim().to_string();
+    let mark = ui
+        .add_enabled(!label.is_empty(), egui::Button::new("Mark"))
+        .on_hover_text("Add a marker with this note at the current time on all plots");
+    if (mark.clicked() || entered) && !label.is_empty() {
+        let placed = state.data_buffer.lock().is_ok_and(|mut buffer| buffer.add_event(label));
+        if placed {
+            state.marker_text.clear();
+        } else if let Ok(mut log) = state.log_buffer.lock() {
+            log.push_log_level(LogLevel::Warn, "No telemetry yet to place the marker on".to_string());
+        }
+    }
+}
+
+/// Writes the telemetry buffer to a CSV file and reports the outcome in the log
+fn export_telemetry(state: &AppState, persistent_settings: &PersistentSettings) {
+    let metadata = export::SessionMetadata::new(&state.port_path, state.baud_rate, persistent_settings);
+    let mut buffer = state.data_buffer.lock().unwrap();
+    let samples = buffer.data.len();
+    match export::export_csv(&buffer, &metadata) {
+        Ok(path) => buffer.push_log(format!("Exported {} samples to {}", samples, path.display())),
+        Err(e) => buffer.push_log(format!("Export failed: {}", e)),
+    }
+}
+
+/// Writes a bug report file and reports the outcome in the log
+fn export_bug_report(state: &AppState, persistent_settings: &PersistentSettings) {
+    let metadata = export::SessionMetadata::new(&state.port_path, state.baud_rate, persistent_settings);
+    let connection = state
+        .connection_state
+        .lock()
+        .map(|s| s.describe())
+        .unwrap_or_default();
+    let result = {
+        let buffer = state.data_buffer.lock().unwrap();
+        let log = state.log_buffer.lock().unwrap();
+        export::export_bug_report(&buffer, &log, &metadata, &connection)
+    };
+    let mut log = state.log_buffer.lock().unwrap();
+    match result {
+        Ok(path) => log.push_log(format!("Bug report saved to {}", path.display())),
+        Err(e) => log.push_log_level(LogLevel::Error, format!("Bug report failed: {}", e)),
+    }
+}
+
+/// Renders the main sections (viewport, commands, logs) in the configured order,
+/// sharing the row between the visible ones
 fn render_main_sections(
     ui: &mut egui::Ui,
     state: &mut AppState,
     command_queue: &CommandQueue,
     persistent_settings: &mut PersistentSettings,
 ) {
+    let sections = persistent_settings.visible_sections();
+    if sections.is_empty() {
+        return;
+    }
     ui.horizontal_top(|ui| {
         let available_width = ui.available_width();
-        let left_width = available_width * 0.25; // 25% for 3D view
-        let middle_width = available_width * 0.20; // 20% for commands
-        let right_width = available_width * 0.55; // 55% for logs
-
-        // 3D Viewport Section
-        ui.group(|ui| {
-            panels::render_viewport_section(ui, state, left_width);
-        });
+        let shares = persistent_settings.section_shares();
+        let visible_share: f32 = sections.iter().map(|s| shares[s.index()]).sum();
+        // Width a share of 1.0 would get, so hidden sections' space goes to the others
+        let full_width = available_width / visible_share.max(f32::EPSILON);
 
-        // Flight Controller Commands Section
-        ui.group(|ui| {
-            panels::render_commands_section(
-                ui,
-                state,
-                command_queue,
-                persistent_settings,
-                middle_width,
-            );
-        });
+        let mut dividers = Vec::new();
+        for (i, &section) in sections.iter().enumerate() {
+            let width = full_width * shares[section.index()];
+            let group = ui.group(|ui| match section {
+                MainSection::Viewport => panels::render_viewport_section(ui, state, persistent_settings, width),
+                MainSection::Commands => {
+                    panels::render_commands_section(ui, state, command_queue, persistent_settings, width)
+                }
+                MainSection::Logs => panels::render_logs_section(ui, state, persistent_settings, width),
+            });
+            if let Some(&next) = sections.get(i + 1) {
+                let action = section_divider(ui, group.response.rect.height());
+                dividers.push((section, next, action));
+            }
+        }
 
-        // System Logs Section
-        ui.group(|ui| {
-            panels::render_logs_section(ui, state, right_width);
-        });
+        apply_divider_drag(persistent_settings, &dividers, full_width);
     });
 }
+
+/// Smallest share of the row any section can be dragged down to
+const MIN_SECTION_FRACTION: f32 = 0.1;
+
+/// What a divider did this frame
+#[derive(Clone, Copy, PartialEq)]
+enum DividerAction {
+    None,
+    Drag(f32),
+    Reset,
+}
+
+/// Thin draggable divider between sections. Double-click restores the default layout.
+fn section_divider(ui: &mut egui::Ui, height: f32) -> DividerAction {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(6.0, height), egui::Sense::click_and_drag());
+    let response = response
+        .on_hover_cursor(egui::CursorIcon::ResizeHorizontal)
+        .on_hover_text("Drag to resize, double-click to reset");
+    let stroke = if response.hovered() || response.dragged() {
+        ui.visuals().widgets.hovered.fg_stroke
+    } else {
+        ui.visuals().widgets.noninteractive.bg_stroke
+    };
+    ui.painter().vline(rect.center().x, rect.y_range(), stroke);
+
+    if response.double_clicked() {
+        DividerAction::Reset
+    } else if response.dragged() {
+        DividerAction::Drag(response.drag_delta().x)
+    } else {
+        DividerAction::None
+    }
+}
+
+/// Moves the section boundaries by the dragged amount, keeping every section usable.
+/// Each divider trades width between the sections on either side of it.
+fn apply_divider_drag(
+    persistent_settings: &mut PersistentSettings,
+    dividers: &[(MainSection, MainSection, DividerAction)],
+    full_width: f32,
+) {
+    if dividers.iter().any(|&(_, _, action)| action == DividerAction::Reset) {
+        persistent_settings.section_fractions = PersistentSettings::default().section_fractions;
+        return;
+    }
+    if full_width <= 0.0 {
+        return;
+    }
+
+    let mut shares = persistent_settings.section_shares();
+    let mut changed = false;
+    for &(left, right, action) in dividers {
+        let DividerAction::Drag(dx) = action else {
+            continue;
+        };
+        let (left, right) = (left.index(), right.index());
+        let delta = (dx / full_width)
+            .max(MIN_SECTION_FRACTION - shares[left])
+            .min(shares[right] - MIN_SECTION_FRACTION);
+        shares[left] += delta;
+        shares[right] -= delta;
+        changed = true;
+    }
+    if changed {
+        persistent_settings.section_fractions = [shares[0], shares[1]];
+    }
+}
@@ -1,12 +1,18 @@
-use crate::app::{AppState, CommandQueue};
+use bevy::log::error;
+use crate::app::{AppState, CommandQueue, hex_bytes};
+use crate::config::MAX_SEND_BYTES;
+use crate::parser::parse_hex_payload;
 use crate::persistence::PersistentSettings;
-use crate::protocol;
-use bevy_egui::egui::{self, DragValue};
+use crate::protocol::{self, CalibrationProgress};
+use crate::telemetry::LogLevel;
+use crate::uart::UartCommand;
+use crate::ui::widgets::NumberInput;
+use bevy_egui::egui::{self, Color32};
 
 /// Renders the flight controller commands section
 pub fn render_commands_section(
     ui: &mut egui::Ui,
-    state: &AppState,
+    state: &mut AppState,
     command_queue: &CommandQueue,
     persistent_settings: &mut PersistentSettings,
     width: f32,
@@ -16,27 +22,91 @@ pub fn render_commands_section(
         ui.heading("FC Commands");
 
         if state.uart_sender.is_some() {
-            render_command_buttons(ui, command_queue);
+            let calibration = state.data_buffer.lock().unwrap().commands.calibration;
+            render_command_buttons(ui, command_queue, calibration, persistent_settings);
+            ui.separator();
+            // Config changes mid-calibration would disturb the bias measurement
+            ui.add_enabled_ui(calibration.is_none(), |ui| {
+                render_flight_config_controls(ui, state, command_queue, persistent_settings);
+            });
             ui.separator();
-            render_flight_config_controls(ui, state, command_queue, persistent_settings);
+            render_outstanding_commands(ui, state, command_queue, persistent_settings);
+            ui.separator();
+            render_manual_send(ui, state);
         } else {
             ui.label("Connect to serial port to enable commands");
         }
     });
 }
 
-/// Calibrate IMU button
-fn render_command_buttons(ui: &mut egui::Ui, command_queue: &CommandQueue) {
+/// Calibrate IMU button, or the progress of the running calibration
+fn render_command_buttons(
+    ui: &mut egui::Ui,
+    command_queue: &CommandQueue,
+    calibration: Option<CalibrationProgress>,
+    persistent_settings: &mut PersistentSettings,
+) {
     ui.horizontal(|ui| {
-        if ui.button("Calibrate IMU").clicked() {
-            if let Err(e) = protocol::send_command_calibrate(command_queue) {
-                eprintln!("{}", e);
+        let Some(calibration) = calibration else {
+            if ui.button("Calibrate IMU").clicked() {
+                if let Err(e) = protocol::send_command_calibrate(command_queue) {
+                    error!("{}", e);
+                }
+            }
+            ui.label("Calibrate gyro/accel bias");
+            ui.add(
+                NumberInput::new(&mut persistent_settings.calibration_timeout_secs, 1.0..=120.0)
+                    .speed(0.5)
+                    .step(1.0)
+                    .prefix("timeout ")
+                    .suffix(" s")
+                    .touch(persistent_settings.touch_mode),
+            )
+            .on_hover_text("Give up waiting for CAL DONE after this long");
+            return;
+        };
+
+        let elapsed = calibration.started.elapsed().as_secs_f32();
+        match calibration.percent {
+            Some(percent) => {
+                ui.add(
+                    egui::ProgressBar::new(percent / 100.0)
+                        .desired_width(160.0)
+                        .text(format!("Calibrating {:.0}%", percent)),
+                );
+            }
+            None => {
+                ui.spinner();
+                ui.label("Calibrating…");
             }
         }
-        ui.label("Calibrate gyro/accel bias");
+        ui.weak(format!("{:.1}s", elapsed))
+            .on_hover_text("Keep the drone still; commands are disabled until the firmware reports CAL DONE");
+        ui.ctx().request_repaint();
     });
 }
 
+/// Queues config + all PID axes as one batch and logs what was sent
+fn send_all(state: &AppState, command_queue: &CommandQueue, persistent_settings: &PersistentSettings) {
+    let config = persistent_settings.to_config_packet();
+    let pids = protocol::SelectPID::ALL.map(|axis| (axis, persistent_settings.get_pid(axis).to_controller()));
+    let gains = pids
+        .iter()
+        .map(|(axis, pid)| format!("{:?} {:.2}/{:.2}/{:.2}", axis, pid.p, pid.i, pid.d))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if let Ok(mut buffer) = state.data_buffer.lock() {
+        let count = protocol::send_all_config(
+            command_queue,
+            &mut buffer.commands,
+            config,
+            pids,
+            persistent_settings.ack_timeout(),
+        );
+        buffer.push_log(format!("Send All ({} commands): {}; PID {}", count, config.summary(), gains));
+    }
+}
+
 fn render_flight_config_controls(
     ui: &mut egui::Ui,
     state: &AppState,
@@ -44,23 +114,16 @@ fn render_flight_config_controls(
     persistent_settings: &mut PersistentSettings,
 ) {
     ui.label("Flight Config");
+    let touch = persistent_settings.touch_mode;
 
     ui.horizontal(|ui| {
         ui.label("Hover Throttle");
-        ui.add(
-            DragValue::new(&mut persistent_settings.throttle_hover)
-                .range(0.05..=0.95)
-                .speed(0.01),
-        );
+        ui.add(NumberInput::new(&mut persistent_settings.throttle_hover, 0.05..=0.95).touch(touch));
     });
 
     ui.horizontal(|ui| {
         ui.label("Throttle Expo");
-        ui.add(
-            DragValue::new(&mut persistent_settings.throttle_expo)
-                .range(0.0..=1.0)
-                .speed(0.01),
-        );
+        ui.add(NumberInput::new(&mut persistent_settings.throttle_expo, 0.0..=1.0).touch(touch));
     });
 
     let max_angle_deg = |rad: f32| rad.to_degrees();
@@ -70,7 +133,7 @@ fn render_flight_config_controls(
         ui.label("Max Roll");
         let mut deg = max_angle_deg(persistent_settings.max_roll_angle);
         if ui
-            .add(DragValue::new(&mut deg).range(5.0..=60.0).speed(0.5).suffix("°"))
+            .add(NumberInput::new(&mut deg, 5.0..=60.0).speed(0.5).step(1.0).suffix("°").touch(touch))
             .changed()
         {
             persistent_settings.max_roll_angle = deg_to_rad(deg);
@@ -81,7 +144,7 @@ fn render_flight_config_controls(
         ui.label("Max Pitch");
         let mut deg = max_angle_deg(persistent_settings.max_pitch_angle);
         if ui
-            .add(DragValue::new(&mut deg).range(5.0..=60.0).speed(0.5).suffix("°"))
+            .add(NumberInput::new(&mut deg, 5.0..=60.0).speed(0.5).step(1.0).suffix("°").touch(touch))
             .changed()
         {
             persistent_settings.max_pitch_angle = deg_to_rad(deg);
@@ -92,7 +155,7 @@ fn render_flight_config_controls(
         ui.label("Max Yaw Rate");
         let mut deg_s = max_angle_deg(persistent_settings.max_yaw_rate);
         if ui
-            .add(DragValue::new(&mut deg_s).range(10.0..=360.0).speed(1.0).suffix("°/s"))
+            .add(NumberInput::new(&mut deg_s, 10.0..=360.0).speed(1.0).step(5.0).suffix("°/s").touch(touch))
             .changed()
         {
             persistent_settings.max_yaw_rate = deg_to_rad(deg_s);
@@ -103,18 +166,155 @@ fn render_flight_config_controls(
         if ui.button("Send Config").clicked() {
             let config = persistent_settings.to_config_packet();
             if let Err(e) = protocol::send_command_config(command_queue, config) {
-                eprintln!("Failed to send config: {}", e);
+                error!("Failed to send config: {}", e);
             } else if let Ok(mut buffer) = state.data_buffer.lock() {
                 buffer.push_log("Flight config sent".to_string());
             }
         }
 
+        let invalid_axis = protocol::SelectPID::ALL.into_iter().find(|&axis| {
+            !persistent_settings
+                .get_pid(axis)
+                .check(&persistent_settings.pid_warning_thresholds)
+                .errors
+                .is_empty()
+        });
+        if ui
+            .add_enabled(invalid_axis.is_none(), egui::Button::new("Send All"))
+            .on_hover_text("Flight config plus the PID gains of every axis, applied once all are ACKed")
+            .on_disabled_hover_text(format!(
+                "{:?} PID gains have errors; fix them in the PID tuning window",
+                invalid_axis.unwrap_or_default()
+            ))
+            .clicked()
+        {
+            send_all(state, command_queue, persistent_settings);
+        }
+        if let Some(batch) = state.data_buffer.lock().ok().and_then(|b| b.commands.batch) {
+            ui.spinner();
+            ui.weak(format!("{}/{} ACKed", batch.acked, batch.expected));
+        }
+
         if ui.button("Save").clicked() {
             if let Err(e) = protocol::send_command_save(command_queue) {
-                eprintln!("Failed to send save: {}", e);
+                error!("Failed to send save: {}", e);
             } else if let Ok(mut buffer) = state.data_buffer.lock() {
                 buffer.push_log("Save to flash queued".to_string());
             }
         }
     });
+
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut persistent_settings.config_refresh_enabled, "Resend every")
+            .on_hover_text("Periodically resend the flight config in case the drone rebooted");
+        ui.add_enabled(
+            persistent_settings.config_refresh_enabled,
+            NumberInput::new(&mut persistent_settings.config_refresh_secs, 1.0..=60.0)
+                .speed(0.1)
+                .step(1.0)
+                .suffix(" s")
+                .touch(persistent_settings.touch_mode),
+        );
+    });
+    ui.checkbox(&mut persistent_settings.push_config_on_connect, "Push config on connect")
+        .on_hover_text("Send the flight config once whenever the serial port opens");
+}
+
+/// Free-form send of ASCII text or hex bytes, for poking at firmware commands
+fn render_manual_send(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label("Send Data");
+        ui.selectable_value(&mut state.manual_send_hex, false, "ASCII");
+        ui.selectable_value(&mut state.manual_send_hex, true, "Hex");
+    });
+
+    let payload = if state.manual_send_hex {
+        parse_hex_payload(&state.manual_send_text)
+    } else {
+        Ok(state.manual_send_text.as_bytes().to_vec())
+    };
+
+    ui.horizontal(|ui| {
+        let hint = if state.manual_send_hex { "DE AD BE EF" } else { "text" };
+        ui.add(egui::TextEdit::singleline(&mut state.manual_send_text).hint_text(hint));
+
+        let valid = match &payload {
+            Ok(bytes) => {
+                let over = bytes.len() > MAX_SEND_BYTES;
+                let text = format!("{}/{} B", bytes.len(), MAX_SEND_BYTES);
+                let label = if over { ui.colored_label(Color32::RED, text) } else { ui.label(text) };
+                label.on_hover_text("Bytes written to the link; they are sent as entered, without framing");
+                !bytes.is_empty() && !over
+            }
+            Err(e) => {
+                ui.colored_label(Color32::RED, e);
+                false
+            }
+        };
+
+        if ui.add_enabled(valid, egui::Button::new("Send")).clicked()
+            && let (Ok(bytes), Some(sender)) = (payload, &state.uart_sender)
+        {
+            let hex = hex_bytes(&bytes);
+            match sender.send(UartCommand::Send { data: bytes }) {
+                Ok(()) => {
+                    if let Ok(mut buffer) = state.data_buffer.lock() {
+                        buffer.push_log_level(LogLevel::Sent, format!("→ FC: raw [{}]", hex));
+                    }
+                }
+                Err(e) => error!("Failed to send data: {}", e),
+            }
+        }
+    });
+}
+
+/// Commands still waiting for an ACK, with retry/dismiss for timed-out ones
+fn render_outstanding_commands(
+    ui: &mut egui::Ui,
+    state: &AppState,
+    command_queue: &CommandQueue,
+    persistent_settings: &mut PersistentSettings,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Awaiting ACK");
+        ui.add(
+            NumberInput::new(&mut persistent_settings.ack_timeout_secs, 0.2..=30.0)
+                .speed(0.1)
+                .step(0.5)
+                .prefix("timeout ")
+                .suffix(" s")
+                .touch(persistent_settings.touch_mode),
+        );
+    });
+
+    let Ok(mut buffer) = state.data_buffer.lock() else {
+        return;
+    };
+    if buffer.commands.outstanding.is_empty() {
+        ui.label(egui::RichText::new("All commands acknowledged").small().color(egui::Color32::GRAY));
+        return;
+    }
+
+    let mut dismiss = None;
+    for pending in &buffer.commands.outstanding {
+        ui.horizontal(|ui| {
+            let age = pending.sent_at.elapsed().as_secs_f32();
+            let text = format!("#{} {} ({:.1}s)", pending.seq, pending.command.describe(), age);
+            if pending.timed_out {
+                ui.colored_label(egui::Color32::from_rgb(230, 200, 60), text);
+                if ui.small_button("Retry").clicked() {
+                    command_queue.enqueue_uncoalesced(pending.command);
+                    dismiss = Some(pending.seq);
+                }
+                if ui.small_button("Dismiss").clicked() {
+                    dismiss = Some(pending.seq);
+                }
+            } else {
+                ui.label(text);
+            }
+        });
+    }
+    if let Some(seq) = dismiss {
+        buffer.commands.remove(seq);
+    }
 }
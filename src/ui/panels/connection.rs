@@ -1,33 +1,56 @@
 use bevy_egui::egui;
 use crate::app::AppState;
+use crate::config::COMMON_BAUD_RATES;
+use crate::persistence::{ConnectionProfile, PersistentSettings, SettingsReset, Theme};
+use crate::protocol;
+use crate::transport::TransportKind;
+use crate::uart::ConnectionState;
 
-/// Renders the top connection panel with serial controls
+/// Renders the top connection panel with serial/TCP link controls
 pub fn render_connection_panel(
     ui: &mut egui::Ui,
     state: &mut AppState,
+    persistent_settings: &mut PersistentSettings,
 ) {
     ui.horizontal_wrapped(|ui| {
         ui.heading("Drone Telemetry Monitor");
         ui.separator();
 
-        // Serial connection
-        ui.label("Serial Port:");
-        let combo_resp = egui::ComboBox::from_id_salt("serial_port_select")
-            .selected_text(&state.port_path)
+        let mut port_committed = false;
+        egui::ComboBox::from_id_salt("transport_select")
+            .selected_text(state.transport.label())
             .show_ui(ui, |ui| {
-                let available = state.available_ports.clone();
-                for port in &available {
-                    ui.selectable_value(&mut state.port_path, port.clone(), port);
+                for kind in TransportKind::ALL {
+                    port_committed |= ui.selectable_value(&mut state.transport, kind, kind.label()).clicked();
                 }
-                ui.separator();
-                ui.label("Or enter manually:");
-                ui.text_edit_singleline(&mut state.port_path);
             });
-        if combo_resp.response.clicked() {
-            state.refresh_ports();
+
+        match state.transport {
+            TransportKind::Serial => port_committed |= render_serial_selector(ui, state),
+            TransportKind::Tcp => {
+                ui.label("Address:");
+                let address = egui::TextEdit::singleline(&mut state.tcp_address)
+                    .hint_text("host:port")
+                    .desired_width(140.0);
+                port_committed |= ui.add(address).lost_focus();
+            }
         }
-        if ui.button("⟳").on_hover_text("Refresh port list").clicked() {
-            state.refresh_ports();
+        port_committed |= render_profile_switcher(ui, state, persistent_settings);
+        state.store_link_settings(persistent_settings);
+
+        render_status_dot(ui, state);
+        render_firmware_version(ui, state);
+
+        if state.port_changed_since_connect() {
+            let auto = port_committed && persistent_settings.reconnect_on_config_change;
+            if auto
+                || ui
+                    .button("Reconnect")
+                    .on_hover_text("Port settings changed, reconnect to apply")
+                    .clicked()
+            {
+                state.reconnect_uart();
+            }
         }
 
         if state.serial_connected {
@@ -35,15 +58,24 @@ pub fn render_connection_panel(
                 state.disconnect_uart();
             }
         } else if ui.button("Connect").clicked() {
-            match state.start_uart_thread() {
-                Ok(()) => {}
-                Err(e) => {
-                    eprintln!("Serial connection failed: {}", e);
-                    if let Ok(mut buffer) = state.data_buffer.lock() {
-                        buffer.push_log(format!("Serial Error: {}", e));
-                    }
-                }
-            }
+            state.start_uart_thread();
+        }
+
+        ui.checkbox(&mut persistent_settings.reconnect_on_config_change, "Auto-reconnect")
+            .on_hover_text("Reopen the port immediately when its settings change");
+        ui.checkbox(&mut persistent_settings.auto_connect_on_launch, "Connect on launch")
+            .on_hover_text("Open the last-used link when the app starts");
+
+        let mut raw_capture = state.raw_capture;
+        if ui
+            .checkbox(&mut raw_capture, "Raw capture")
+            .on_hover_text(format!(
+                "Hex dump every received byte to {}",
+                crate::app::raw_capture_dir().display()
+            ))
+            .changed()
+        {
+            state.set_raw_capture(raw_capture);
         }
 
         ui.separator();
@@ -53,5 +85,248 @@ pub fn render_connection_panel(
         if ui.button("PID Tuning").clicked() {
             state.show_pid_tuning = !state.show_pid_tuning;
         }
+        if ui.button("Config").on_hover_text("Show the flight config that would be sent").clicked() {
+            state.show_config_summary = !state.show_config_summary;
+        }
+
+        ui.separator();
+        egui::ComboBox::from_id_salt("theme_select")
+            .selected_text(persistent_settings.theme.label())
+            .show_ui(ui, |ui| {
+                for theme in Theme::ALL {
+                    ui.selectable_value(&mut persistent_settings.theme, theme, theme.label());
+                }
+            });
+        ui.menu_button("Layout", |ui| render_layout_menu(ui, persistent_settings));
+        ui.menu_button("Settings", |ui| render_settings_menu(ui, state, persistent_settings));
     });
 }
+
+/// Reset-to-defaults entries (confirmed in a separate window) and the undo for the last one
+fn render_settings_menu(ui: &mut egui::Ui, state: &mut AppState, persistent_settings: &mut PersistentSettings) {
+    ui.label("Reset to defaults:");
+    for scope in SettingsReset::ALL {
+        if ui.button(format!("{}…", scope.label())).clicked() {
+            state.pending_settings_reset = Some(scope);
+            ui.close_menu();
+        }
+    }
+    ui.separator();
+    let undo = ui
+        .add_enabled(state.settings_undo.is_some(), egui::Button::new("Undo reset"))
+        .on_hover_text("Restore the settings as they were before the last reset");
+    if undo.clicked()
+        && let Some((scope, previous)) = state.settings_undo.take()
+    {
+        *persistent_settings = previous;
+        if let Ok(mut log) = state.log_buffer.lock() {
+            log.push_log(format!("Undid reset of {}", scope.label().to_lowercase()));
+        }
+        ui.close_menu();
+    }
+}
+
+/// Order and visibility of the main sections, and whether the plots are shown
+fn render_layout_menu(ui: &mut egui::Ui, persistent_settings: &mut PersistentSettings) {
+    ui.label("Sections, left to right:");
+    let order = persistent_settings.section_order;
+    let mut swap = None;
+    for (i, section) in order.into_iter().enumerate() {
+        ui.horizontal(|ui| {
+            let mut shown = !persistent_settings.hidden_sections.contains(&section);
+            if ui.checkbox(&mut shown, section.label()).changed() {
+                persistent_settings.hidden_sections.retain(|&s| s != section);
+                if !shown {
+                    persistent_settings.hidden_sections.push(section);
+                }
+            }
+            if ui.add_enabled(i > 0, egui::Button::new("⬆").small()).clicked() {
+                swap = Some((i - 1, i));
+            }
+            if ui.add_enabled(i + 1 < order.len(), egui::Button::new("⬇").small()).clicked() {
+                swap = Some((i, i + 1));
+            }
+        });
+    }
+    if let Some((a, b)) = swap {
+        persistent_settings.section_order.swap(a, b);
+    }
+
+    ui.separator();
+    ui.checkbox(&mut persistent_settings.plots_collapsed, "Collapse plots");
+    ui.checkbox(&mut persistent_settings.touch_mode, "Touch mode")
+        .on_hover_text("Bigger text, spacing and stepper controls for touchscreens");
+    if ui.button("Reset layout").clicked() {
+        let defaults = PersistentSettings::default();
+        persistent_settings.section_order = defaults.section_order;
+        persistent_settings.hidden_sections.clear();
+        persistent_settings.plots_collapsed = false;
+        persistent_settings.section_fractions = defaults.section_fractions;
+        ui.close_menu();
+    }
+}
+
+/// Serial port and baud rate pickers. Returns true when a selection was committed.
+fn render_serial_selector(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    ui.label("Serial Port:");
+    let mut port_committed = false;
+    let combo_resp = egui::ComboBox::from_id_salt("serial_port_select")
+        .selected_text(&state.port_path)
+        .show_ui(ui, |ui| {
+            let available = state.available_ports.clone();
+            for port in &available {
+                port_committed |= ui.selectable_value(&mut state.port_path, port.clone(), port).clicked();
+            }
+            ui.separator();
+            ui.label("Or enter manually:");
+            port_committed |= ui.text_edit_singleline(&mut state.port_path).lost_focus();
+        });
+    if combo_resp.response.clicked() {
+        state.refresh_ports();
+    }
+    if ui.button("⟳").on_hover_text("Refresh port list").clicked() {
+        state.refresh_ports();
+    }
+
+    egui::ComboBox::from_id_salt("baud_rate_select")
+        .selected_text(format!("{} baud", state.baud_rate))
+        .show_ui(ui, |ui| {
+            for baud in COMMON_BAUD_RATES {
+                port_committed |= ui
+                    .selectable_value(&mut state.baud_rate, baud, baud.to_string())
+                    .clicked();
+            }
+        });
+    port_committed
+}
+
+/// Quick-switch dropdown for saved connection profiles, with save/delete.
+/// Returns true when a profile was applied.
+fn render_profile_switcher(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    persistent_settings: &mut PersistentSettings,
+) -> bool {
+    let current = persistent_settings
+        .connection_profiles
+        .iter()
+        .find(|p| p.port_path == state.port_path && p.baud_rate == state.baud_rate)
+        .map(|p| p.name.clone());
+
+    let mut applied = false;
+    let mut delete = None;
+    egui::ComboBox::from_id_salt("connection_profile_select")
+        .selected_text(current.as_deref().unwrap_or("Profile…"))
+        .show_ui(ui, |ui| {
+            for (i, profile) in persistent_settings.connection_profiles.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let selected = current.as_deref() == Some(profile.name.as_str());
+                    if ui
+                        .selectable_label(selected, &profile.name)
+                        .on_hover_text(format!("{} @ {} baud", profile.port_path, profile.baud_rate))
+                        .clicked()
+                    {
+                        state.apply_profile(profile);
+                        applied = true;
+                    }
+                    if ui.small_button("🗑").on_hover_text("Delete profile").clicked() {
+                        delete = Some(i);
+                    }
+                });
+            }
+            if !persistent_settings.connection_profiles.is_empty() {
+                ui.separator();
+            }
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.new_profile_name)
+                        .hint_text("Profile name")
+                        .desired_width(100.0),
+                );
+                let name = state.new_profile_name.trim().to_string();
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new("Save current"))
+                    .clicked()
+                {
+                    let profile = ConnectionProfile {
+                        name: name.clone(),
+                        port_path: state.port_path.clone(),
+                        baud_rate: state.baud_rate,
+                    };
+                    let profiles = &mut persistent_settings.connection_profiles;
+                    match profiles.iter_mut().find(|p| p.name == name) {
+                        Some(existing) => *existing = profile,
+                        None => profiles.push(profile),
+                    }
+                    state.new_profile_name.clear();
+                }
+            });
+        });
+    if let Some(i) = delete {
+        persistent_settings.connection_profiles.remove(i);
+    }
+    applied
+}
+
+/// Firmware version reported on connect, in the warning color when its protocol differs
+fn render_firmware_version(ui: &mut egui::Ui, state: &AppState) {
+    if !state.serial_connected {
+        return;
+    }
+    let version = state.data_buffer.lock().ok().and_then(|b| b.firmware_version.clone());
+    match version {
+        Some(version) if version.is_incompatible() => {
+            ui.colored_label(ui.visuals().warn_fg_color, format!("FW {}", version.text))
+                .on_hover_text(format!(
+                    "Firmware protocol v{} differs from this GUI's v{}; commands are not sent",
+                    version.protocol().unwrap_or_default(),
+                    protocol::PROTOCOL_VERSION
+                ));
+        }
+        Some(version) => {
+            ui.label(format!("FW {}", version.text));
+        }
+        None => {
+            ui.weak("FW ?").on_hover_text("The firmware has not answered the version query");
+        }
+    }
+}
+
+/// Colored dot summarising the link: gray = disconnected, yellow = opening or
+/// open but no telemetry in the last second, green = telemetry flowing, red = error
+fn render_status_dot(ui: &mut egui::Ui, state: &AppState) {
+    let connection_state = state
+        .connection_state
+        .lock()
+        .map(|s| s.clone())
+        .unwrap_or_default();
+    let telemetry_age = state
+        .data_buffer
+        .lock()
+        .ok()
+        .and_then(|b| b.last_sample_at)
+        .map(|t| t.elapsed());
+
+    let (color, detail) = match &connection_state {
+        ConnectionState::Disconnected => (egui::Color32::GRAY, None),
+        ConnectionState::Opening => (egui::Color32::YELLOW, None),
+        ConnectionState::Error(_) => (egui::Color32::RED, None),
+        ConnectionState::Connected => match telemetry_age {
+            Some(age) if age.as_secs_f32() < 1.0 => (egui::Color32::GREEN, Some("telemetry flowing".to_string())),
+            Some(age) => (
+                egui::Color32::YELLOW,
+                Some(format!("no telemetry for {:.1}s", age.as_secs_f32())),
+            ),
+            None => (egui::Color32::YELLOW, Some("no telemetry received yet".to_string())),
+        },
+    };
+
+    let hover = match detail {
+        Some(detail) => format!("{}, {}", connection_state.describe(), detail),
+        None => connection_state.describe(),
+    };
+
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(14.0, 14.0), egui::Sense::hover());
+    ui.painter().circle_filled(rect.center(), 5.0, color);
+    response.on_hover_text(hover);
+}
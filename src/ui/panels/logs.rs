@@ -1,33 +1,263 @@
 use bevy_egui::egui;
-use crate::app::AppState;
+use crate::app::{AppState, LogScrollAnchor};
+use crate::persistence::PersistentSettings;
+use crate::log_rules::{LogRule, RuleCondition};
+use crate::logging::LogTarget;
+use crate::telemetry::{LogLevel, LogMessage, RawPacketKind, TelemetryData};
 
 /// Renders the system logs section
 pub fn render_logs_section(
     ui: &mut egui::Ui,
-    state: &AppState,
+    state: &mut AppState,
+    persistent_settings: &mut PersistentSettings,
     width: f32,
 ) {
     ui.vertical(|ui| {
         ui.set_width(width);
-        let mut buffer = state.data_buffer.lock().unwrap();
-        ui.label(format!("System Logs ({} messages)", buffer.logs.len()));
-
-        egui::ScrollArea::vertical()
-            .max_height(200.0)
-            .id_salt("system_logs")
-            .auto_shrink([false; 2])
-            .stick_to_bottom(state.auto_scroll_logs)
-            .show(ui, |ui| {
+        {
+            let mut buffer = state.log_buffer.lock().unwrap();
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "System Logs ({} messages, {} muted)",
+                    buffer.logs.len(),
+                    buffer.muted_count
+                ));
+                ui.separator();
+                ui.checkbox(&mut state.show_log_info, "Info");
+                ui.checkbox(&mut state.show_log_warn, "Warn");
+                ui.checkbox(&mut state.show_log_error, "Error");
+                ui.checkbox(&mut state.show_log_sent, "Sent");
+            });
+
+            let mut jump_to_latest = false;
+            ui.horizontal(|ui| {
                 if ui.button("clear logs").clicked() {
-                    buffer.clear_logs();
+                    buffer.clear();
                 }
+                ui.label("Keep");
+                ui.add(
+                    egui::DragValue::new(&mut persistent_settings.log_scrollback)
+                        .range(10..=10_000)
+                        .suffix(" lines"),
+                );
+                if state.log_scroll_anchor.is_some_and(|a| !a.at_bottom)
+                    && ui.button("⬇ Jump to latest").clicked()
+                {
+                    jump_to_latest = true;
+                }
+            });
 
-                for log in buffer.logs.iter() {
+            let rows: Vec<&LogMessage> = buffer
+                .logs
+                .iter()
+                .filter(|l| state.log_level_visible(l.level))
+                .collect();
+            let row_height = ui.spacing().interact_size.y;
+            let row_stride = row_height + ui.spacing().item_spacing.y;
+
+            let mut scroll = egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .id_salt("system_logs")
+                .auto_shrink([false; 2])
+                .stick_to_bottom(state.auto_scroll_logs);
+            if jump_to_latest {
+                scroll = scroll.vertical_scroll_offset(rows.len() as f32 * row_stride);
+            } else if let Some(anchor) = state.log_scroll_anchor {
+                // Lines above the anchor may have been dropped from the scrollback
+                let index = rows.iter().position(|l| l.seq >= anchor.seq).unwrap_or(rows.len());
+                scroll = scroll.vertical_scroll_offset(index as f32 * row_stride + anchor.row_offset);
+            }
+
+            let output = scroll.show_rows(ui, row_height, rows.len(), |ui, range| {
+                for log in &rows[range] {
                     ui.horizontal(|ui| {
                         ui.label(format!("[{}]", log.clock_time.format("%H:%M:%S%.3f")));
-                        ui.label(&log.message);
+                        ui.colored_label(level_color(ui.visuals(), log.level), &log.message);
                     });
                 }
             });
+
+            let offset = output.state.offset.y;
+            let at_bottom = offset + output.inner_rect.height() >= output.content_size.y - 1.0;
+            let top = (offset / row_stride) as usize;
+            state.log_scroll_anchor = match rows.get(top) {
+                Some(log) if !(at_bottom && state.auto_scroll_logs) => Some(LogScrollAnchor {
+                    seq: log.seq,
+                    row_offset: offset - top as f32 * row_stride,
+                    at_bottom,
+                }),
+                _ => None,
+            };
+        }
+
+        render_raw_packets(ui, state);
+        render_mute_patterns(ui, state, persistent_settings);
+        render_log_rules(ui, persistent_settings);
+        render_flight_log_settings(ui, persistent_settings);
     });
 }
+
+/// Recent lines and frames exactly as received, including ones the parser rejects
+fn render_raw_packets(ui: &mut egui::Ui, state: &AppState) {
+    egui::CollapsingHeader::new("Raw Packets")
+        .id_salt("raw_packets")
+        .show(ui, |ui| {
+            let Ok(buffer) = state.log_buffer.lock() else {
+                return;
+            };
+            ui.label(format!("{} most recent", buffer.raw_packets.len()));
+            let row_height = ui.spacing().interact_size.y;
+            egui::ScrollArea::vertical()
+                .max_height(160.0)
+                .id_salt("raw_packets_scroll")
+                .auto_shrink([false, true])
+                .stick_to_bottom(true)
+                .show_rows(ui, row_height, buffer.raw_packets.len(), |ui, range| {
+                    for packet in buffer.raw_packets.range(range) {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("📋").on_hover_text("Copy").clicked() {
+                                ui.ctx().copy_text(packet.text.clone());
+                            }
+                            ui.label(format!("[{}]", packet.clock_time.format("%H:%M:%S%.3f")));
+                            let kind = packet.kind_label();
+                            if matches!(packet.kind, RawPacketKind::Frame { crc_ok: false, .. }) {
+                                ui.colored_label(ui.visuals().warn_fg_color, kind);
+                            } else {
+                                ui.weak(kind);
+                            }
+                            ui.weak(format!("{} B", packet.len));
+                            ui.add(egui::Label::new(egui::RichText::new(&packet.text).monospace()).truncate());
+                        });
+                    }
+                });
+        });
+}
+
+/// Editor for rules that log telemetry events (e.g. height crossing zero)
+fn render_log_rules(ui: &mut egui::Ui, persistent_settings: &mut PersistentSettings) {
+    egui::CollapsingHeader::new("Telemetry log rules")
+        .id_salt("telemetry_log_rules")
+        .show(ui, |ui| {
+            let mut remove = None;
+            for (i, rule) in persistent_settings.log_rules.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut rule.enabled, "");
+                    egui::ComboBox::from_id_salt(("log_rule_field", i))
+                        .selected_text(rule.field.as_str())
+                        .show_ui(ui, |ui| {
+                            for name in TelemetryData::FIELD_NAMES {
+                                ui.selectable_value(&mut rule.field, name.to_string(), name);
+                            }
+                        });
+                    egui::ComboBox::from_id_salt(("log_rule_condition", i))
+                        .selected_text(rule.condition.label())
+                        .show_ui(ui, |ui| {
+                            for condition in RuleCondition::ALL {
+                                ui.selectable_value(&mut rule.condition, condition, condition.label());
+                            }
+                        });
+                    ui.add(egui::DragValue::new(&mut rule.threshold).speed(0.01));
+                    if ui.small_button("✖").on_hover_text("Remove rule").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                persistent_settings.log_rules.remove(i);
+            }
+            if ui.button("Add rule").clicked() {
+                persistent_settings.log_rules.push(LogRule::default());
+            }
+        });
+}
+
+/// Options for teeing the log panel into rotating files under the config dir
+fn render_flight_log_settings(ui: &mut egui::Ui, persistent_settings: &mut PersistentSettings) {
+    egui::CollapsingHeader::new("Log file")
+        .id_salt("flight_log_settings")
+        .show(ui, |ui| {
+            ui.checkbox(&mut persistent_settings.flight_log_enabled, "Write logs to disk")
+                .on_hover_text(format!(
+                    "Appends to {}",
+                    crate::persistence::app_config_dir().join("logs").display()
+                ));
+            ui.add_enabled_ui(persistent_settings.flight_log_enabled, |ui| {
+                ui.checkbox(
+                    &mut persistent_settings.flight_log_include_frame_errors,
+                    "Include corrupt telemetry frames",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Rotate at");
+                    ui.add(
+                        egui::DragValue::new(&mut persistent_settings.flight_log_max_kb)
+                            .range(16..=65536)
+                            .suffix(" KiB"),
+                    );
+                    ui.label("keep");
+                    ui.add(egui::DragValue::new(&mut persistent_settings.flight_log_keep).range(0..=50));
+                    ui.label("old files");
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.label("App diagnostics:");
+                egui::ComboBox::from_id_salt("log_target")
+                    .selected_text(persistent_settings.log_target.label())
+                    .show_ui(ui, |ui| {
+                        for target in LogTarget::ALL {
+                            ui.selectable_value(&mut persistent_settings.log_target, target, target.label());
+                        }
+                    });
+            })
+            .response
+            .on_hover_text("Where the app's own messages go; takes effect on restart");
+        });
+}
+
+fn level_color(visuals: &egui::Visuals, level: LogLevel) -> egui::Color32 {
+    match level {
+        LogLevel::Info => visuals.weak_text_color(),
+        LogLevel::Warn => visuals.warn_fg_color,
+        LogLevel::Error => visuals.error_fg_color,
+        LogLevel::Sent if visuals.dark_mode => egui::Color32::from_rgb(90, 180, 230),
+        LogLevel::Sent => egui::Color32::from_rgb(20, 110, 170),
+    }
+}
+
+/// Editor for the substrings that suppress matching log lines
+fn render_mute_patterns(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    persistent_settings: &mut PersistentSettings,
+) {
+    egui::CollapsingHeader::new("Muted patterns")
+        .id_salt("log_mute_patterns")
+        .show(ui, |ui| {
+            let mut remove = None;
+            for (i, pattern) in persistent_settings.log_mute_patterns.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.monospace(pattern);
+                    if ui.small_button("✖").on_hover_text("Unmute").clicked() {
+                        remove = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = remove {
+                persistent_settings.log_mute_patterns.remove(i);
+            }
+
+            ui.horizontal(|ui| {
+                let resp = ui.add(
+                    egui::TextEdit::singleline(&mut state.new_mute_pattern)
+                        .hint_text("substring, e.g. heartbeat ok")
+                        .desired_width(180.0),
+                );
+                let submitted = resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.button("Mute").clicked() || submitted) && !state.new_mute_pattern.is_empty() {
+                    let pattern = std::mem::take(&mut state.new_mute_pattern);
+                    if !persistent_settings.log_mute_patterns.contains(&pattern) {
+                        persistent_settings.log_mute_patterns.push(pattern);
+                    }
+                }
+            });
+        });
+}
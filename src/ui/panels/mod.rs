@@ -7,5 +7,8 @@ pub mod viewport;
 pub use commands::render_commands_section;
 pub use connection::render_connection_panel;
 pub use logs::render_logs_section;
-pub use plots::{render_attitude_plot, render_gyro_plot, render_motor_plot, render_pid_plot, render_velocity_plot};
+pub use plots::{
+    finish_plot_cursor, render_attitude_plot, render_custom_plot, render_esc_plot, render_fft_plot, render_gyro_plot, render_motor_plot,
+    render_pid_plot, render_plot_window_selector, render_position_plot, render_rate_plot, render_velocity_plot,
+};
 pub use viewport::render_viewport_section;
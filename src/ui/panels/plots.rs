@@ -1,8 +1,25 @@
-use crate::app::AppState;
-use crate::telemetry::PidAxis;
+use crate::app::{AppState, PlotCapture};
+use crate::config::{ESC_TEMP_COOL_C, ESC_TEMP_HOT_C};
+use crate::persistence::{PersistentSettings, PlotStyle};
+use crate::spectrum;
+use crate::telemetry::{
+    self, AngleUnit, DataBuffer, PidAxis, PidTerm, SeriesPoints, TelemetryData, XAxisMode, YawDisplay,
+};
 use bevy_egui::egui;
 use egui::Color32;
-use egui_plot::{Legend, Line, Plot, PlotPoint, Text};
+use egui_plot::{Legend, Line, Plot, PlotPoint, PlotPoints, Points, Polygon, Text, VLine};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+// Series colors, picked to stay readable on both dark and light plot backgrounds
+const RED: Color32 = Color32::from_rgb(230, 60, 60);
+const GREEN: Color32 = Color32::from_rgb(40, 170, 70);
+const BLUE: Color32 = Color32::from_rgb(60, 120, 240);
+const YELLOW: Color32 = Color32::from_rgb(215, 160, 0);
+const GRAY: Color32 = Color32::from_rgb(140, 140, 140);
+const MARKER_COLOR: Color32 = Color32::from_rgb(200, 90, 220);
+/// How close (in points) the pointer must be to a marker line to show its label
+const MARKER_HOVER_PX: f32 = 4.0;
+const OSCILLATION_FILL: Color32 = Color32::from_rgba_premultiplied(90, 20, 20, 60);
 
 /// Find local peaks (maxima and minima) in plot data.
 /// Returns (x, y) pairs for points that are local extrema,
@@ -41,235 +58,1132 @@ fn plot_peaks(plot_ui: &mut egui_plot::PlotUi, data: &[[f64; 2]], color: Color32
     }
 }
 
-/// True when the buffer has at least two distinct timestamps — egui_plot 0.29
+/// True when the visible samples span at least two distinct x values — egui_plot 0.29
 /// panics with "Bad final plot bounds" if x_min == x_max.
-fn has_plottable_range(data: &std::collections::VecDeque<crate::telemetry::TelemetryData>) -> bool {
-    if data.len() < 2 {
+fn has_plottable_range(buffer: &DataBuffer, view: PlotView) -> bool {
+    let mut xs = buffer.visible(view.window).map(|d| view.x_mode.x(d));
+    let Some(first) = xs.next() else {
         return false;
+    };
+    xs.any(|x| x != first)
+}
+
+/// View options shared by every plot
+#[derive(Clone, Copy)]
+struct PlotView {
+    window: Option<f32>,
+    x_mode: XAxisMode,
+    angle_unit: AngleUnit,
+    /// Pixel columns to decimate series down to; None draws every sample
+    columns: Option<usize>,
+}
+
+impl PlotView {
+    fn of(ui: &egui::Ui, state: &AppState) -> Self {
+        Self {
+            window: state.plot_window_secs,
+            x_mode: state.plot_x_mode,
+            angle_unit: state.angle_unit,
+            columns: (!state.plot_full_resolution).then(|| ui.available_width().max(1.0) as usize),
+        }
+    }
+
+    /// Fingerprint of everything besides the samples that shapes a series
+    fn cache_key(self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.window.map(f32::to_bits).hash(&mut hasher);
+        self.x_mode.hash(&mut hasher);
+        self.angle_unit.hash(&mut hasher);
+        self.columns.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn decimate(self, points: Vec<[f64; 2]>) -> Vec<[f64; 2]> {
+        match self.columns {
+            Some(columns) => decimate(points, columns),
+            None => points,
+        }
+    }
+}
+
+/// Builds `[x, y]` plot points for the visible samples, reusing the points cached
+/// under `id` until new telemetry arrives
+fn series(buffer: &DataBuffer, view: PlotView, id: &str, value: impl Fn(&TelemetryData) -> f64) -> SeriesPoints {
+    buffer.cached_series(id, view.cache_key(), || {
+        view.decimate(
+            buffer
+                .visible(view.window)
+                .map(|d| [view.x_mode.x(d), value(d)])
+                .collect(),
+        )
+    })
+}
+
+/// Like `series` for a yaw angle (rad), either unwrapped into a continuous heading
+/// or folded into ±180°
+fn yaw_series(
+    buffer: &DataBuffer,
+    view: PlotView,
+    id: &str,
+    display: YawDisplay,
+    yaw: fn(&TelemetryData) -> f32,
+) -> SeriesPoints {
+    let id = format!("{}_{:?}", id, display);
+    buffer.cached_series(&id, view.cache_key(), || {
+        let mut heading: Option<(f64, f64)> = None;
+        let points = buffer
+            .visible(view.window)
+            .map(|d| {
+                let raw = yaw(d) as f64;
+                let value = match display {
+                    YawDisplay::Wrapped => telemetry::wrap_angle(raw),
+                    // Add each step's shortest turn, so crossing ±180° keeps going
+                    YawDisplay::Continuous => {
+                        let unwrapped = match heading {
+                            Some((prev_raw, prev)) => prev + telemetry::wrap_angle(raw - prev_raw),
+                            None => raw,
+                        };
+                        heading = Some((raw, unwrapped));
+                        unwrapped
+                    }
+                };
+                [view.x_mode.x(d), view.angle_unit.convert(value)]
+            })
+            .collect();
+        view.decimate(points)
+    })
+}
+
+/// Reduces a series denser than the plot's pixel columns to the min and max of each
+/// column's run of points, in x order, so peaks survive with far fewer vertices
+fn decimate(points: Vec<[f64; 2]>, columns: usize) -> Vec<[f64; 2]> {
+    if columns == 0 || points.len() <= columns * 2 {
+        return points;
     }
-    let first = data.front().unwrap().timestamp;
-    data.iter().any(|d| d.timestamp != first)
+    let run_len = points.len().div_ceil(columns);
+    let mut out = Vec::with_capacity(columns * 2 + 1);
+    for run in points.chunks(run_len) {
+        let mut lo = run[0];
+        let mut hi = run[0];
+        for &p in run {
+            if p[1] < lo[1] {
+                lo = p;
+            }
+            if p[1] > hi[1] {
+                hi = p;
+            }
+        }
+        let (first, second) = if lo[0] <= hi[0] { (lo, hi) } else { (hi, lo) };
+        out.push(first);
+        if second != first {
+            out.push(second);
+        }
+    }
+    // Keep the newest sample so the line still ends at the current value
+    if out.last() != points.last() {
+        out.extend(points.last());
+    }
+    out
+}
+
+/// Converts samples to a staircase: each value is held until the next sample
+fn to_steps(points: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let mut steps = Vec::with_capacity(points.len() * 2);
+    for pair in points.windows(2) {
+        steps.push(pair[0]);
+        steps.push([pair[1][0], pair[0][1]]);
+    }
+    steps.extend(points.last());
+    steps
+}
+
+/// Line through the samples, drawn as stairs when `stepped`. The plot takes its
+/// own copy of the points, so they are converted straight from the slice.
+fn plot_line(points: &[[f64; 2]], stepped: bool) -> Line {
+    if stepped {
+        Line::new(to_steps(points))
+    } else {
+        Line::new(PlotPoints::from_iter(points.iter().copied()))
+    }
+}
+
+/// Per-plot "Steps" toggle; returns whether the plot draws stairs
+fn step_toggle(ui: &mut egui::Ui, state: &mut AppState, plot_id: &'static str) -> bool {
+    let mut stepped = state.stepped_plots.contains(plot_id);
+    if ui
+        .checkbox(&mut stepped, "Steps")
+        .on_hover_text("Hold each value until the next sample instead of interpolating")
+        .changed()
+    {
+        if stepped {
+            state.stepped_plots.insert(plot_id);
+        } else {
+            state.stepped_plots.remove(plot_id);
+        }
+    }
+    stepped
+}
+
+/// Button that saves the plot as PNG once it has been drawn this frame
+fn export_button(ui: &mut egui::Ui, state: &mut AppState, plot_id: &'static str) {
+    if ui
+        .button("Export Plot")
+        .on_hover_text("Save this plot, with title and legend, as a PNG in the exports folder")
+        .clicked()
+    {
+        state.plot_capture = Some(PlotCapture { plot_id, rect: None });
+    }
+}
+
+/// Hands the plot's screen area to a pending export of this plot
+fn offer_capture_rect(ui: &egui::Ui, state: &mut AppState, plot_id: &'static str, rect: egui::Rect) {
+    if let Some(capture) = &mut state.plot_capture
+        && capture.plot_id == plot_id
+    {
+        let ppp = ui.ctx().pixels_per_point();
+        capture.rect = Some(egui::Rect::from_min_max(rect.min * ppp, rect.max * ppp));
+    }
+}
+
+/// A named signal for the stats row under a plot
+type StatsSignal<'a> = (&'a str, Color32, &'a dyn Fn(&TelemetryData) -> f64);
+
+/// Compact min/max/mean/current readout over the visible window, one entry per signal
+fn render_stats_row(ui: &mut egui::Ui, buffer: &DataBuffer, window: Option<f32>, signals: &[StatsSignal]) {
+    ui.horizontal_wrapped(|ui| {
+        for (name, color, value) in signals {
+            if let Some(stats) = buffer.stats(window, value) {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{name}: min {:.2}  max {:.2}  mean {:.2}  now {:.2}",
+                        stats.min, stats.max, stats.mean, stats.current
+                    ))
+                    .monospace()
+                    .small()
+                    .color(*color),
+                );
+                ui.add_space(8.0);
+            }
+        }
+    });
+}
+
+/// Label and (commanded, measured) values read from a sample
+type LagSignal = (&'static str, fn(&TelemetryData) -> (f32, f32));
+
+/// Commanded/measured pairs whose response lag is estimated
+const LAG_SIGNALS: [LagSignal; 3] = [
+    ("Throttle→motors", |d| (d.input_throttle, (d.motor1 + d.motor2 + d.motor3 + d.motor4) / 4.0)),
+    ("Roll", |d| (d.input_roll, d.roll)),
+    ("Pitch", |d| (d.input_pitch, d.pitch)),
+];
+
+/// Estimated delay from each command to its measured response over the visible window
+fn render_lag_row(ui: &mut egui::Ui, buffer: &DataBuffer, view: PlotView) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label(egui::RichText::new("Response lag:").monospace().small())
+            .on_hover_text("Delay that best lines up changes in the command with the measured response");
+        for (name, signal) in LAG_SIGNALS {
+            // Cached as a single [lag_ms, correlation] point, empty when there is no estimate
+            let estimate = buffer.cached_series(&format!("lag_{}", name), view.cache_key(), || {
+                let samples: Vec<(f64, f64, f64)> = buffer
+                    .visible(view.window)
+                    .map(|d| {
+                        let (command, response) = signal(d);
+                        (d.timestamp as f64 / 1000.0, command as f64, response as f64)
+                    })
+                    .collect();
+                spectrum::estimate_lag(&samples).map(|(lag, r)| vec![[lag, r]]).unwrap_or_default()
+            });
+            let text = match estimate.first() {
+                Some([lag, r]) => format!("{} {:.0} ms (r {:.2})", name, lag, r),
+                None => format!("{} —", name),
+            };
+            ui.label(egui::RichText::new(text).monospace().small());
+            ui.add_space(8.0);
+        }
+    });
+}
+
+/// Hover label listing the sample nearest the pointer: its wall-clock time and `describe(sample)`
+fn nearest_sample_label(buffer: &DataBuffer, view: PlotView, x: f64, describe: impl Fn(&TelemetryData) -> String) -> String {
+    match buffer.nearest_sample(view.x_mode, x) {
+        Some(d) => format!("{}\n{}", d.clock_time.format("%H:%M:%S%.3f"), describe(d)),
+        None => String::new(),
+    }
+}
+
+/// Draws the vertical cursor shared by all plots at the last hovered x
+fn draw_cursor(plot_ui: &mut egui_plot::PlotUi, cursor_x: Option<f64>) {
+    if let Some(x) = cursor_x {
+        plot_ui.vline(VLine::new(x).color(Color32::from_gray(160)).width(1.0));
+    }
+}
+
+/// Draws a vertical line at each timeline marker; `track_hover` shows their labels
+fn draw_event_markers(plot_ui: &mut egui_plot::PlotUi, events: &[(f64, &str)]) {
+    for &(x, _) in events {
+        plot_ui.vline(VLine::new(x).color(MARKER_COLOR).width(1.5).name("Marker"));
+    }
+}
+
+/// Records the x coordinate under the pointer if this plot is hovered, and shows the
+/// label of an event marker the pointer is over
+fn track_hover<R>(
+    ui: &egui::Ui,
+    hover_x: &mut Option<f64>,
+    response: &egui_plot::PlotResponse<R>,
+    events: &[(f64, &str)],
+) {
+    let Some(pos) = response.response.hover_pos() else {
+        return;
+    };
+    *hover_x = Some(response.transform.value_from_position(pos).x);
+
+    let labels: Vec<&str> = events
+        .iter()
+        .filter(|&&(x, _)| (response.transform.position_from_point_x(x) - pos.x).abs() <= MARKER_HOVER_PX)
+        .map(|&(_, label)| label)
+        .collect();
+    if !labels.is_empty() {
+        egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), response.response.id.with("marker"), |ui| {
+            for label in labels {
+                ui.label(label);
+            }
+        });
+    }
+}
+
+/// Promotes this frame's hovered x to the cursor drawn next frame; clears it when no plot is hovered
+pub fn finish_plot_cursor(state: &mut AppState) {
+    state.plot_cursor_x = state.plot_hover_x.take();
+}
+
+/// Preset choices for the shared plot time window
+const PLOT_WINDOWS: [Option<f32>; 6] = [None, Some(5.0), Some(10.0), Some(30.0), Some(60.0), Some(120.0)];
+
+fn window_label(window: Option<f32>) -> String {
+    match window {
+        None => "All".to_string(),
+        Some(secs) => format!("Last {secs:.0} s"),
+    }
+}
+
+/// Selector for the time window and x-axis shared by all plots
+pub fn render_plot_window_selector(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    persistent_settings: &mut PersistentSettings,
+) {
+    ui.checkbox(&mut state.show_rate_plot, "Rates");
+    ui.checkbox(&mut state.show_fft_plot, "FFT");
+    ui.separator();
+    ui.label("X axis:");
+    ui.selectable_value(&mut state.plot_x_mode, XAxisMode::Time, "Time");
+    ui.selectable_value(&mut state.plot_x_mode, XAxisMode::SampleIndex, "Samples");
+    ui.label("Angles:");
+    ui.selectable_value(&mut state.angle_unit, AngleUnit::Degrees, "deg");
+    ui.selectable_value(&mut state.angle_unit, AngleUnit::Radians, "rad");
+
+    ui.label("Window:");
+    egui::ComboBox::from_id_salt("plot_window")
+        .selected_text(window_label(state.plot_window_secs))
+        .show_ui(ui, |ui| {
+            for window in PLOT_WINDOWS {
+                ui.selectable_value(&mut state.plot_window_secs, window, window_label(window));
+            }
+        });
+
+    ui.checkbox(&mut state.plot_full_resolution, "Full resolution")
+        .on_hover_text("Draw every sample; by default long series are reduced to a min/max envelope per pixel");
+
+    ui.menu_button("Style", |ui| render_plot_style(ui, &mut persistent_settings.plot_style));
+
+    ui.label("PNG width:");
+    ui.add(
+        egui::DragValue::new(&mut persistent_settings.plot_export_width)
+            .range(320..=7680)
+            .suffix(" px"),
+    );
+}
+
+/// Editor for the roll/pitch/yaw colors shared by plots, readouts and the 3D model
+fn render_plot_style(ui: &mut egui::Ui, style: &mut PlotStyle) {
+    egui::Grid::new("plot_style").num_columns(2).show(ui, |ui| {
+        for (label, rgb) in [
+            ("Roll / X", &mut style.roll),
+            ("Pitch / Y", &mut style.pitch),
+            ("Yaw / Z", &mut style.yaw),
+            ("3D front marker", &mut style.front_marker),
+        ] {
+            ui.label(label);
+            ui.color_edit_button_srgb(rgb);
+            ui.end_row();
+        }
+        ui.label("Line width");
+        ui.add(egui::DragValue::new(&mut style.line_width).range(0.5..=6.0).speed(0.1));
+        ui.end_row();
+    });
+    ui.horizontal(|ui| {
+        if ui.button("Default").clicked() {
+            *style = PlotStyle::default();
+        }
+        if ui.button("Colorblind-safe").clicked() {
+            *style = PlotStyle::colorblind();
+        }
+    });
 }
 
 /// Renders the attitude plot (Roll, Pitch, Yaw)
-pub fn render_attitude_plot(ui: &mut egui::Ui, state: &AppState) {
+pub fn render_attitude_plot(ui: &mut egui::Ui, state: &mut AppState, persistent_settings: &mut PersistentSettings) {
     let max_width = ui.ctx().screen_rect().width() - 32.0;
     ui.set_max_width(max_width);
-    ui.group(|ui| {
+    let group = ui.group(|ui| {
         ui.set_max_width(max_width - 16.0);
-        ui.label("Attitude (Roll, Pitch, Yaw)");
+        ui.horizontal(|ui| {
+            ui.label(format!("Attitude (Roll, Pitch, Yaw, {})", state.angle_unit.name()));
+            ui.checkbox(&mut state.show_setpoints, "Show setpoints");
+            egui::ComboBox::from_id_salt("yaw_display")
+                .selected_text(format!("Yaw: {}", state.yaw_display.label()))
+                .show_ui(ui, |ui| {
+                    for display in YawDisplay::ALL {
+                        ui.selectable_value(&mut state.yaw_display, display, display.label());
+                    }
+                });
+            step_toggle(ui, state, "attitude_plot");
+            export_button(ui, state, "attitude_plot");
+            ui.separator();
+            ui.checkbox(&mut persistent_settings.deviation_alarm_enabled, "Warn when off setpoint by")
+                .on_hover_text("Logs a warning when roll or pitch doesn't follow its setpoint");
+            ui.add(
+                egui::DragValue::new(&mut persistent_settings.deviation_margin_deg)
+                    .range(1.0..=90.0)
+                    .speed(0.5)
+                    .suffix("°"),
+            );
+            ui.label("for");
+            ui.add(
+                egui::DragValue::new(&mut persistent_settings.deviation_hold_secs)
+                    .range(0.05..=10.0)
+                    .speed(0.05)
+                    .suffix(" s"),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut persistent_settings.oscillation_detect_enabled, "Mark oscillation above")
+                .on_hover_text("Shades and logs stretches where roll or pitch error keeps swinging past the amplitude");
+            ui.add(
+                egui::DragValue::new(&mut persistent_settings.oscillation_amplitude_deg)
+                    .range(0.5..=45.0)
+                    .speed(0.1)
+                    .suffix("°"),
+            );
+            ui.label("within");
+            ui.add(
+                egui::DragValue::new(&mut persistent_settings.oscillation_window_secs)
+                    .range(0.1..=10.0)
+                    .speed(0.05)
+                    .suffix(" s"),
+            );
+        });
+        let show_setpoints = state.show_setpoints;
+        let yaw_display = state.yaw_display;
+        let stepped = state.stepped_plots.contains("attitude_plot");
+        let view = PlotView::of(ui, state);
+        let cursor_x = state.plot_cursor_x;
         let buffer = state.data_buffer.lock().unwrap();
-        if !has_plottable_range(&buffer.data) {
+        let events = buffer.event_markers(view.x_mode);
+        if !has_plottable_range(&buffer, view) {
             ui.label("Waiting for telemetry…");
             return;
         }
         let plot_height = (ui.ctx().screen_rect().height() * 0.25).min(300.0);
         let plot_width = ui.available_width();
 
-        let roll_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.roll as f64]).collect();
-        let pitch_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.pitch as f64]).collect();
-        let yaw_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.yaw as f64]).collect();
-        let roll_sp: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.input_roll as f64]).collect();
-        let pitch_sp: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.input_pitch as f64]).collect();
-        let yaw_sp: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.input_yaw as f64]).collect();
+        let unit = view.angle_unit;
+        let angle = move |rad: f32| unit.convert(rad as f64);
+        let roll_data = series(&buffer, view, "roll", |d| angle(d.roll));
+        let pitch_data = series(&buffer, view, "pitch", |d| angle(d.pitch));
+        let yaw_data = yaw_series(&buffer, view, "yaw", yaw_display, |d| d.yaw);
+        let roll_sp = series(&buffer, view, "roll_sp", |d| angle(d.input_roll));
+        let pitch_sp = series(&buffer, view, "pitch_sp", |d| angle(d.input_pitch));
+        let yaw_sp = yaw_series(&buffer, view, "yaw_sp", yaw_display, |d| d.input_yaw);
+        // Label peaks that stand out by about a degree
+        let peak_prominence = unit.convert(1f64.to_radians());
+        let gaps: Vec<f64> = buffer
+            .visible(view.window)
+            .filter(|d| d.gap_before_ms.is_some())
+            .map(|d| view.x_mode.x(d))
+            .collect();
+        let first_x = roll_data.first().map_or(0.0, |p| p[0]);
+        let oscillations: Vec<(&str, f64, f64)> = buffer
+            .oscillations
+            .iter()
+            .map(|o| {
+                let (start, end) = o.span(view.x_mode);
+                (o.axis, start, end)
+            })
+            .filter(|&(_, _, end)| end >= first_x)
+            .collect();
+        // Shade the regions over the plotted attitude range, so they don't widen the auto bounds
+        let (y_min, y_max) = [&roll_data, &pitch_data, &yaw_data]
+            .into_iter()
+            .flat_map(|series| series.iter())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p[1]), hi.max(p[1])));
 
-        Plot::new("attitude_plot")
+        let [r_color, p_color, y_color] = persistent_settings.plot_style.axis_colors();
+        let width = persistent_settings.plot_style.line_width;
+        let response = Plot::new("attitude_plot")
             .legend(Legend::default())
+            .label_formatter(|_, point| {
+                nearest_sample_label(&buffer, view, point.x, |d| {
+                    let unit = view.angle_unit;
+                    format!(
+                        "Roll {} (SP {})\nPitch {} (SP {})\nYaw {} (SP {})",
+                        unit.format(d.roll),
+                        unit.format(d.input_roll),
+                        unit.format(d.pitch),
+                        unit.format(d.input_pitch),
+                        unit.format(d.yaw),
+                        unit.format(d.input_yaw)
+                    )
+                })
+            })
             .height(plot_height)
             .width(plot_width)
             .show(ui, |plot_ui| {
-                let r_color = Color32::from_rgb(255, 0, 0);
-                let p_color = Color32::from_rgb(0, 255, 0);
-                let y_color = Color32::from_rgb(0, 0, 255);
-                plot_ui.line(Line::new(roll_data.clone()).name("Roll").color(r_color));
-                plot_ui.line(Line::new(pitch_data.clone()).name("Pitch").color(p_color));
-                plot_ui.line(Line::new(yaw_data.clone()).name("Yaw").color(y_color));
-                plot_ui.line(Line::new(roll_sp).name("Roll SP").color(r_color.gamma_multiply(0.5)).style(egui_plot::LineStyle::dashed_dense()));
-                plot_ui.line(Line::new(pitch_sp).name("Pitch SP").color(p_color.gamma_multiply(0.5)).style(egui_plot::LineStyle::dashed_dense()));
-                plot_ui.line(Line::new(yaw_sp).name("Yaw SP").color(y_color.gamma_multiply(0.5)).style(egui_plot::LineStyle::dashed_dense()));
-                plot_peaks(plot_ui, &roll_data, r_color, 1.0);
-                plot_peaks(plot_ui, &pitch_data, p_color, 1.0);
-                plot_peaks(plot_ui, &yaw_data, y_color, 1.0);
+                draw_cursor(plot_ui, cursor_x);
+                draw_event_markers(plot_ui, &events);
+                for (i, &x) in gaps.iter().enumerate() {
+                    let marker = VLine::new(x).color(YELLOW).style(egui_plot::LineStyle::dotted_dense());
+                    // One legend entry for all gap markers
+                    plot_ui.vline(if i == 0 { marker.name("Gap") } else { marker });
+                }
+                // Regions share a legend entry per axis
+                for &(axis, start, end) in &oscillations {
+                    plot_ui.polygon(
+                        Polygon::new(vec![[start, y_min], [end, y_min], [end, y_max], [start, y_max]])
+                            .name(format!("{} oscillation", axis))
+                            .fill_color(OSCILLATION_FILL)
+                            .stroke(egui::Stroke::NONE),
+                    );
+                }
+                plot_ui.line(plot_line(&roll_data, stepped).name("Roll").color(r_color).width(width));
+                plot_ui.line(plot_line(&pitch_data, stepped).name("Pitch").color(p_color).width(width));
+                plot_ui.line(plot_line(&yaw_data, stepped).name("Yaw").color(y_color).width(width));
+                if show_setpoints {
+                    plot_ui.line(plot_line(&roll_sp, stepped).name("Roll SP").color(r_color.gamma_multiply(0.5)).style(egui_plot::LineStyle::dashed_dense()));
+                    plot_ui.line(plot_line(&pitch_sp, stepped).name("Pitch SP").color(p_color.gamma_multiply(0.5)).style(egui_plot::LineStyle::dashed_dense()));
+                    plot_ui.line(plot_line(&yaw_sp, stepped).name("Yaw SP").color(y_color.gamma_multiply(0.5)).style(egui_plot::LineStyle::dashed_dense()));
+                }
+                plot_peaks(plot_ui, &roll_data, r_color, peak_prominence);
+                plot_peaks(plot_ui, &pitch_data, p_color, peak_prominence);
+                plot_peaks(plot_ui, &yaw_data, y_color, peak_prominence);
             });
+        render_stats_row(ui, &buffer, view.window, &[
+            ("Roll", r_color, &|d| angle(d.roll)),
+            ("Pitch", p_color, &|d| angle(d.pitch)),
+            ("Yaw", y_color, &|d| angle(d.yaw)),
+        ]);
+        render_lag_row(ui, &buffer, view);
+        track_hover(ui, &mut state.plot_hover_x, &response, &events);
     });
+    offer_capture_rect(ui, state, "attitude_plot", group.response.rect);
 }
 
 /// Renders the gyro rate plot (X, Y, Z angular velocity)
-pub fn render_gyro_plot(ui: &mut egui::Ui, state: &AppState) {
+pub fn render_gyro_plot(ui: &mut egui::Ui, state: &mut AppState, persistent_settings: &mut PersistentSettings) {
+    let max_width = ui.ctx().screen_rect().width() - 32.0;
+    ui.set_max_width(max_width);
+    ui.group(|ui| {
+        ui.set_max_width(max_width - 16.0);
+        let stepped = ui
+            .horizontal(|ui| {
+                ui.label(format!("Gyro Rates ({}/s)", state.angle_unit.name()));
+                step_toggle(ui, state, "gyro_plot")
+            })
+            .inner;
+        render_vibration_gauge(ui, state, persistent_settings);
+        let view = PlotView::of(ui, state);
+        let cursor_x = state.plot_cursor_x;
+        let buffer = state.data_buffer.lock().unwrap();
+        let events = buffer.event_markers(view.x_mode);
+        if !has_plottable_range(&buffer, view) {
+            ui.label("Waiting for telemetry…");
+            return;
+        }
+        let plot_height = (ui.ctx().screen_rect().height() * 0.20).min(200.0);
+        let plot_width = ui.available_width();
+
+        let unit = view.angle_unit;
+        let gx_data = series(&buffer, view, "gyro_x", |d| unit.convert(d.gyro_x as f64));
+        let gy_data = series(&buffer, view, "gyro_y", |d| unit.convert(d.gyro_y as f64));
+        let gz_data = series(&buffer, view, "gyro_z", |d| unit.convert(d.gyro_z as f64));
+        let peak_prominence = unit.convert(5f64.to_radians());
+
+        let [x_color, y_color, z_color] = persistent_settings.plot_style.axis_colors();
+        let width = persistent_settings.plot_style.line_width;
+        let response = Plot::new("gyro_plot")
+            .legend(Legend::default())
+            .height(plot_height)
+            .width(plot_width)
+            .show(ui, |plot_ui| {
+                draw_cursor(plot_ui, cursor_x);
+                draw_event_markers(plot_ui, &events);
+                plot_ui.line(plot_line(&gx_data, stepped).name("Gyro X").color(x_color).width(width));
+                plot_ui.line(plot_line(&gy_data, stepped).name("Gyro Y").color(y_color).width(width));
+                plot_ui.line(plot_line(&gz_data, stepped).name("Gyro Z").color(z_color).width(width));
+                plot_peaks(plot_ui, &gx_data, x_color, peak_prominence);
+                plot_peaks(plot_ui, &gy_data, y_color, peak_prominence);
+                plot_peaks(plot_ui, &gz_data, z_color, peak_prominence);
+            });
+        track_hover(ui, &mut state.plot_hover_x, &response, &events);
+    });
+}
+
+/// Vibration level as a bar relative to the alarm threshold, plus the alarm settings
+fn render_vibration_gauge(ui: &mut egui::Ui, state: &AppState, persistent_settings: &mut PersistentSettings) {
+    let (vibration, high) = {
+        let buffer = state.data_buffer.lock().unwrap();
+        (buffer.vibration, buffer.is_vibration_high())
+    };
+    ui.horizontal(|ui| {
+        ui.label("Vibration:");
+        let threshold = persistent_settings.vibration_threshold.max(f32::EPSILON);
+        let fill = if high {
+            Color32::from_rgb(220, 80, 60)
+        } else if vibration as f32 > threshold * 0.5 {
+            Color32::from_rgb(220, 180, 60)
+        } else {
+            Color32::from_rgb(80, 180, 80)
+        };
+        ui.add(
+            egui::ProgressBar::new((vibration as f32 / threshold).min(1.0))
+                .desired_width(120.0)
+                .fill(fill)
+                .text(format!("{:.1} deg/s", vibration)),
+        )
+        .on_hover_text("RMS of the high-frequency part of the gyro signal");
+        ui.checkbox(&mut persistent_settings.vibration_alarm_enabled, "Warn above");
+        ui.add(
+            egui::DragValue::new(&mut persistent_settings.vibration_threshold)
+                .speed(0.5)
+                .range(1.0..=1000.0)
+                .suffix(" deg/s"),
+        );
+    });
+}
+
+/// Renders the amplitude spectrum of roll/pitch/yaw over the visible window
+pub fn render_fft_plot(ui: &mut egui::Ui, state: &mut AppState, persistent_settings: &PersistentSettings) {
+    if !state.show_fft_plot {
+        return;
+    }
     let max_width = ui.ctx().screen_rect().width() - 32.0;
     ui.set_max_width(max_width);
     ui.group(|ui| {
         ui.set_max_width(max_width - 16.0);
-        ui.label("Gyro Rates (deg/s)");
+        ui.label(format!("Attitude Spectrum ({} vs Hz)", state.angle_unit.name()));
+        let view = PlotView::of(ui, state);
         let buffer = state.data_buffer.lock().unwrap();
-        if !has_plottable_range(&buffer.data) {
+        let key = view.cache_key();
+        // Always by firmware time, whatever the x axis of the other plots
+        let spectrum = |id: &str, angle: fn(&TelemetryData) -> f32| {
+            buffer.cached_series(id, key, || {
+                let samples: Vec<(f64, f64)> = buffer
+                    .visible(view.window)
+                    .map(|d| (d.timestamp as f64 / 1000.0, view.angle_unit.convert(angle(d) as f64)))
+                    .collect();
+                spectrum::compute_fft(&samples)
+            })
+        };
+        let roll = spectrum("fft_roll", |d| d.roll);
+        let pitch = spectrum("fft_pitch", |d| d.pitch);
+        let yaw = spectrum("fft_yaw", |d| d.yaw);
+        if roll.len() < 2 {
+            ui.label("Waiting for telemetry…");
+            return;
+        }
+
+        let [r_color, p_color, y_color] = persistent_settings.plot_style.axis_colors();
+        let width = persistent_settings.plot_style.line_width;
+        Plot::new("fft_plot")
+            .legend(Legend::default())
+            .height((ui.ctx().screen_rect().height() * 0.20).min(200.0))
+            .width(ui.available_width())
+            .x_axis_label("Hz")
+            .include_y(0.0)
+            .show(ui, |plot_ui| {
+                plot_ui.line(plot_line(&roll, false).name("Roll").color(r_color).width(width));
+                plot_ui.line(plot_line(&pitch, false).name("Pitch").color(p_color).width(width));
+                plot_ui.line(plot_line(&yaw, false).name("Yaw").color(y_color).width(width));
+            });
+    });
+}
+
+/// Renders roll/pitch/yaw rates derived from the attitude series
+pub fn render_rate_plot(ui: &mut egui::Ui, state: &mut AppState, persistent_settings: &PersistentSettings) {
+    if !state.show_rate_plot {
+        return;
+    }
+    let max_width = ui.ctx().screen_rect().width() - 32.0;
+    ui.set_max_width(max_width);
+    ui.group(|ui| {
+        ui.set_max_width(max_width - 16.0);
+        ui.label(format!("Attitude Rates ({}/s, derived)", state.angle_unit.name()));
+        let view = PlotView::of(ui, state);
+        let cursor_x = state.plot_cursor_x;
+        let buffer = state.data_buffer.lock().unwrap();
+        let events = buffer.event_markers(view.x_mode);
+        if !has_plottable_range(&buffer, view) {
             ui.label("Waiting for telemetry…");
             return;
         }
         let plot_height = (ui.ctx().screen_rect().height() * 0.20).min(200.0);
         let plot_width = ui.available_width();
 
-        let rad2deg = 180.0 / std::f64::consts::PI;
-        let gx_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.gyro_x as f64 * rad2deg]).collect();
-        let gy_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.gyro_y as f64 * rad2deg]).collect();
-        let gz_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.gyro_z as f64 * rad2deg]).collect();
+        // Rates come back in deg/s
+        let to_points = |rates: Vec<(&TelemetryData, f64)>| -> Vec<[f64; 2]> {
+            view.decimate(
+                rates
+                    .into_iter()
+                    .map(|(d, r)| [view.x_mode.x(d), view.angle_unit.convert(r.to_radians())])
+                    .collect(),
+            )
+        };
+        let key = view.cache_key();
+        let roll_rate = buffer.cached_series("roll_rate", key, || to_points(buffer.get_roll_rate_data(view.window)));
+        let pitch_rate = buffer.cached_series("pitch_rate", key, || to_points(buffer.get_pitch_rate_data(view.window)));
+        let yaw_rate = buffer.cached_series("yaw_rate", key, || to_points(buffer.get_yaw_rate_data(view.window)));
+        // Rates drop the first sample, so re-check the x span
+        if roll_rate.first().map(|p| p[0]) == roll_rate.last().map(|p| p[0]) {
+            ui.label("Waiting for telemetry…");
+            return;
+        }
 
-        Plot::new("gyro_plot")
+        let [r_color, p_color, y_color] = persistent_settings.plot_style.axis_colors();
+        let width = persistent_settings.plot_style.line_width;
+        let response = Plot::new("rate_plot")
             .legend(Legend::default())
             .height(plot_height)
             .width(plot_width)
             .show(ui, |plot_ui| {
-                let x_color = Color32::from_rgb(255, 0, 0);
-                let y_color = Color32::from_rgb(0, 255, 0);
-                let z_color = Color32::from_rgb(0, 0, 255);
-                plot_ui.line(Line::new(gx_data.clone()).name("Gyro X").color(x_color));
-                plot_ui.line(Line::new(gy_data.clone()).name("Gyro Y").color(y_color));
-                plot_ui.line(Line::new(gz_data.clone()).name("Gyro Z").color(z_color));
-                plot_peaks(plot_ui, &gx_data, x_color, 5.0);
-                plot_peaks(plot_ui, &gy_data, y_color, 5.0);
-                plot_peaks(plot_ui, &gz_data, z_color, 5.0);
+                draw_cursor(plot_ui, cursor_x);
+                draw_event_markers(plot_ui, &events);
+                plot_ui.line(plot_line(&roll_rate, false).name("Roll rate").color(r_color).width(width));
+                plot_ui.line(plot_line(&pitch_rate, false).name("Pitch rate").color(p_color).width(width));
+                plot_ui.line(plot_line(&yaw_rate, false).name("Yaw rate").color(y_color).width(width));
             });
+        track_hover(ui, &mut state.plot_hover_x, &response, &events);
     });
 }
 
 /// Renders the velocity + height plot
-pub fn render_velocity_plot(ui: &mut egui::Ui, state: &AppState) {
+pub fn render_velocity_plot(ui: &mut egui::Ui, state: &mut AppState) {
     let max_width = ui.ctx().screen_rect().width() - 32.0;
     ui.set_max_width(max_width);
     ui.group(|ui| {
         ui.set_max_width(max_width - 16.0);
-        ui.label("Velocity (m/s) & Height (m)");
+        let stepped = ui
+            .horizontal(|ui| {
+                ui.label("Velocity (m/s) & Height (m)");
+                step_toggle(ui, state, "velocity_plot")
+            })
+            .inner;
+        let view = PlotView::of(ui, state);
+        let cursor_x = state.plot_cursor_x;
         let buffer = state.data_buffer.lock().unwrap();
-        if !has_plottable_range(&buffer.data) {
+        let events = buffer.event_markers(view.x_mode);
+        if !has_plottable_range(&buffer, view) {
             ui.label("Waiting for telemetry…");
             return;
         }
         let plot_height = (ui.ctx().screen_rect().height() * 0.20).min(200.0);
         let plot_width = ui.available_width();
 
-        let vx_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.vel_x as f64]).collect();
-        let vy_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.vel_y as f64]).collect();
-        let h_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.height as f64]).collect();
+        let vx_data = series(&buffer, view, "vel_x", |d| d.vel_x as f64);
+        let vy_data = series(&buffer, view, "vel_y", |d| d.vel_y as f64);
+        let h_data = series(&buffer, view, "height", |d| d.height as f64);
 
-        Plot::new("velocity_plot")
+        let response = Plot::new("velocity_plot")
             .legend(Legend::default())
             .height(plot_height)
             .width(plot_width)
             .show(ui, |plot_ui| {
-                let vx_color = Color32::from_rgb(255, 100, 100);
-                let vy_color = Color32::from_rgb(100, 255, 100);
-                let h_color = Color32::from_rgb(255, 255, 100);
-                plot_ui.line(Line::new(vx_data.clone()).name("Vel X").color(vx_color));
-                plot_ui.line(Line::new(vy_data.clone()).name("Vel Y").color(vy_color));
-                plot_ui.line(Line::new(h_data.clone()).name("Height").color(h_color));
+                draw_cursor(plot_ui, cursor_x);
+                draw_event_markers(plot_ui, &events);
+                let vx_color = RED;
+                let vy_color = GREEN;
+                let h_color = YELLOW;
+                plot_ui.line(plot_line(&vx_data, stepped).name("Vel X").color(vx_color));
+                plot_ui.line(plot_line(&vy_data, stepped).name("Vel Y").color(vy_color));
+                plot_ui.line(plot_line(&h_data, stepped).name("Height").color(h_color));
                 plot_peaks(plot_ui, &vx_data, vx_color, 0.1);
                 plot_peaks(plot_ui, &vy_data, vy_color, 0.1);
                 plot_peaks(plot_ui, &h_data, h_color, 0.05);
             });
+        track_hover(ui, &mut state.plot_hover_x, &response, &events);
+    });
+}
+
+/// Meters per degree of latitude (and of longitude at the equator)
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Renders the GPS track as a top-down trace in meters from the first fix.
+/// Hidden entirely when no sample in the buffer carries a GPS fix.
+pub fn render_position_plot(ui: &mut egui::Ui, state: &mut AppState) {
+    let view = PlotView::of(ui, state);
+    let buffer = state.data_buffer.lock().unwrap();
+    let fixes: Vec<(f64, f64)> = buffer
+        .visible(view.window)
+        .filter_map(|d| d.lat.zip(d.lon))
+        .collect();
+    let Some(&(lat0, lon0)) = fixes.first() else {
+        return;
+    };
+
+    let max_width = ui.ctx().screen_rect().width() - 32.0;
+    ui.set_max_width(max_width);
+    ui.group(|ui| {
+        ui.set_max_width(max_width - 16.0);
+        ui.label("GPS Track (m from first fix, north up)");
+        let plot_height = (ui.ctx().screen_rect().height() * 0.30).min(300.0);
+        let plot_width = ui.available_width();
+
+        let lon_scale = METERS_PER_DEGREE * lat0.to_radians().cos();
+        let track: Vec<[f64; 2]> = fixes
+            .iter()
+            .map(|(lat, lon)| [(lon - lon0) * lon_scale, (lat - lat0) * METERS_PER_DEGREE])
+            .collect();
+        let current = *track.last().unwrap();
+
+        Plot::new("position_plot")
+            .legend(Legend::default())
+            .height(plot_height)
+            .width(plot_width)
+            .data_aspect(1.0)
+            .show(ui, |plot_ui| {
+                plot_ui.line(
+                    Line::new(track)
+                        .name("Track")
+                        .color(Color32::from_rgb(0, 150, 220)),
+                );
+                plot_ui.points(
+                    Points::new(vec![current])
+                        .name("Current")
+                        .radius(4.0)
+                        .color(YELLOW),
+                );
+            });
     });
 }
 
 /// Renders the motor throttle output plot (M1, M2, M3, M4)
-pub fn render_motor_plot(ui: &mut egui::Ui, state: &AppState) {
+pub fn render_motor_plot(ui: &mut egui::Ui, state: &mut AppState) {
     let max_width = ui.ctx().screen_rect().width() - 32.0;
     ui.set_max_width(max_width);
     ui.group(|ui| {
         ui.set_max_width(max_width - 16.0);
-        ui.label("Motor Outputs (0-1)");
+        let stepped = ui
+            .horizontal(|ui| {
+                ui.label("Motor Outputs (0-1)");
+                step_toggle(ui, state, "motor_plot")
+            })
+            .inner;
+        let view = PlotView::of(ui, state);
+        let cursor_x = state.plot_cursor_x;
         let buffer = state.data_buffer.lock().unwrap();
-        if !has_plottable_range(&buffer.data) {
+        let events = buffer.event_markers(view.x_mode);
+        if !has_plottable_range(&buffer, view) {
             ui.label("Waiting for telemetry…");
             return;
         }
         let plot_height = (ui.ctx().screen_rect().height() * 0.20).min(200.0);
         let plot_width = ui.available_width();
 
-        let m1_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.motor1 as f64]).collect();
-        let m2_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.motor2 as f64]).collect();
-        let m3_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.motor3 as f64]).collect();
-        let m4_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.motor4 as f64]).collect();
-        let thr_input: Vec<[f64; 2]> = buffer.data.iter().map(|d| [d.timestamp as f64 / 1000.0, d.input_throttle as f64]).collect();
+        let m1_data = series(&buffer, view, "motor1", |d| d.motor1 as f64);
+        let m2_data = series(&buffer, view, "motor2", |d| d.motor2 as f64);
+        let m3_data = series(&buffer, view, "motor3", |d| d.motor3 as f64);
+        let m4_data = series(&buffer, view, "motor4", |d| d.motor4 as f64);
+        let thr_input = series(&buffer, view, "input_throttle", |d| d.input_throttle as f64);
 
-        Plot::new("motor_plot")
+        let response = Plot::new("motor_plot")
             .legend(Legend::default())
             .height(plot_height)
             .width(plot_width)
             .show(ui, |plot_ui| {
-                let m1_color = Color32::from_rgb(255, 80, 80);
-                let m2_color = Color32::from_rgb(80, 255, 80);
-                let m3_color = Color32::from_rgb(80, 80, 255);
-                let m4_color = Color32::from_rgb(255, 255, 80);
-                let thr_color = Color32::from_rgb(200, 200, 200);
-                plot_ui.line(Line::new(m1_data.clone()).name("M1").color(m1_color));
-                plot_ui.line(Line::new(m2_data.clone()).name("M2").color(m2_color));
-                plot_ui.line(Line::new(m3_data.clone()).name("M3").color(m3_color));
-                plot_ui.line(Line::new(m4_data.clone()).name("M4").color(m4_color));
-                plot_ui.line(Line::new(thr_input).name("Throttle Input").color(thr_color).style(egui_plot::LineStyle::dashed_dense()));
+                draw_cursor(plot_ui, cursor_x);
+                draw_event_markers(plot_ui, &events);
+                let m1_color = RED;
+                let m2_color = GREEN;
+                let m3_color = BLUE;
+                let m4_color = YELLOW;
+                let thr_color = GRAY;
+                plot_ui.line(plot_line(&m1_data, stepped).name("M1").color(m1_color));
+                plot_ui.line(plot_line(&m2_data, stepped).name("M2").color(m2_color));
+                plot_ui.line(plot_line(&m3_data, stepped).name("M3").color(m3_color));
+                plot_ui.line(plot_line(&m4_data, stepped).name("M4").color(m4_color));
+                plot_ui.line(plot_line(&thr_input, stepped).name("Throttle Input").color(thr_color).style(egui_plot::LineStyle::dashed_dense()));
                 plot_peaks(plot_ui, &m1_data, m1_color, 0.05);
                 plot_peaks(plot_ui, &m2_data, m2_color, 0.05);
                 plot_peaks(plot_ui, &m3_data, m3_color, 0.05);
                 plot_peaks(plot_ui, &m4_data, m4_color, 0.05);
             });
+        track_hover(ui, &mut state.plot_hover_x, &response, &events);
     });
 }
 
-/// Renders the PID plot for the selected axis
-pub fn render_pid_plot(ui: &mut egui::Ui, state: &mut AppState) {
+/// Shades an ESC temperature from green (cool) through yellow to red (hot)
+fn esc_temp_color(temp: f32) -> Color32 {
+    let t = ((temp - ESC_TEMP_COOL_C) / (ESC_TEMP_HOT_C - ESC_TEMP_COOL_C)).clamp(0.0, 1.0);
+    let (from, to, t) = if t < 0.5 { (GREEN, YELLOW, t * 2.0) } else { (YELLOW, RED, t * 2.0 - 1.0) };
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color32::from_rgb(mix(from.r(), to.r()), mix(from.g(), to.g()), mix(from.b(), to.b()))
+}
+
+/// Renders ESC temperatures or currents from `ESC:` lines, with a live per-motor
+/// temperature readout. Hidden until the firmware sends any.
+pub fn render_esc_plot(ui: &mut egui::Ui, state: &mut AppState) {
+    let buffer = state.data_buffer.lock().unwrap();
+    let Some(latest) = buffer.esc.back() else {
+        return;
+    };
     let max_width = ui.ctx().screen_rect().width() - 32.0;
     ui.set_max_width(max_width);
     ui.group(|ui| {
         ui.set_max_width(max_width - 16.0);
         ui.horizontal(|ui| {
-            ui.label("PID Axis:");
-            ui.selectable_value(&mut state.selected_pid_axis, PidAxis::Roll, "Roll");
-            ui.selectable_value(&mut state.selected_pid_axis, PidAxis::Pitch, "Pitch");
-            ui.selectable_value(&mut state.selected_pid_axis, PidAxis::Yaw, "Yaw");
+            ui.label("ESC");
+            ui.selectable_value(&mut state.esc_plot_currents, false, "Temp (°C)");
+            ui.selectable_value(&mut state.esc_plot_currents, true, "Current (A)");
+            ui.separator();
+            for (i, temp) in latest.temps.iter().enumerate() {
+                ui.label(
+                    egui::RichText::new(format!("M{} {:.0}°C", i + 1, temp))
+                        .monospace()
+                        .strong()
+                        .color(esc_temp_color(*temp)),
+                );
+            }
         });
 
-        let selected_axis = state.selected_pid_axis;
-        let axis_name = match selected_axis {
-            PidAxis::Roll => "Roll",
-            PidAxis::Pitch => "Pitch",
-            PidAxis::Yaw => "Yaw",
+        // ESC lines have no firmware timestamp, so x is seconds since the session started
+        let started = buffer.session.started;
+        let currents = state.esc_plot_currents;
+        let motors: [Vec<[f64; 2]>; 4] = std::array::from_fn(|m| {
+            buffer
+                .visible_esc(state.plot_window_secs)
+                .map(|e| {
+                    let x = (e.clock_time - started).num_milliseconds() as f64 / 1000.0;
+                    let y = if currents { e.currents[m] } else { e.temps[m] };
+                    [x, y as f64]
+                })
+                .collect()
+        });
+        if motors[0].first().map(|p| p[0]) == motors[0].last().map(|p| p[0]) {
+            ui.label("Waiting for ESC telemetry…");
+            return;
+        }
+
+        let plot_height = (ui.ctx().screen_rect().height() * 0.20).min(200.0);
+        Plot::new("esc_plot")
+            .legend(Legend::default())
+            .height(plot_height)
+            .width(ui.available_width())
+            .show(ui, |plot_ui| {
+                for ((data, name), color) in motors.iter().zip(["M1", "M2", "M3", "M4"]).zip([RED, GREEN, BLUE, YELLOW]) {
+                    plot_ui.line(plot_line(data, false).name(name).color(color));
+                }
+            });
+    });
+}
+
+/// Renders the PID plot: P/I/D of one axis, or one term across all three axes
+pub fn render_pid_plot(ui: &mut egui::Ui, state: &mut AppState, persistent_settings: &PersistentSettings) {
+    let max_width = ui.ctx().screen_rect().width() - 32.0;
+    ui.set_max_width(max_width);
+    let group = ui.group(|ui| {
+        ui.set_max_width(max_width - 16.0);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut state.pid_plot_combined, "All axes");
+            ui.separator();
+            if state.pid_plot_combined {
+                ui.label("Term:");
+                ui.selectable_value(&mut state.selected_pid_term, PidTerm::P, "P");
+                ui.selectable_value(&mut state.selected_pid_term, PidTerm::I, "I");
+                ui.selectable_value(&mut state.selected_pid_term, PidTerm::D, "D");
+            } else {
+                ui.label("PID Axis:");
+                ui.selectable_value(&mut state.selected_pid_axis, PidAxis::Roll, "Roll");
+                ui.selectable_value(&mut state.selected_pid_axis, PidAxis::Pitch, "Pitch");
+                ui.selectable_value(&mut state.selected_pid_axis, PidAxis::Yaw, "Yaw");
+            }
+            ui.separator();
+            step_toggle(ui, state, "pid_plot");
+            export_button(ui, state, "pid_plot");
+        });
+        let stepped = state.stepped_plots.contains("pid_plot");
+
+        // (legend name, color, axis, term) for each plotted line
+        let signals: [(&str, Color32, PidAxis, PidTerm); 3] = if state.pid_plot_combined {
+            let term = state.selected_pid_term;
+            let [r_color, p_color, y_color] = persistent_settings.plot_style.axis_colors();
+            ui.label(format!("{} Term (Roll, Pitch, Yaw)", term.name()));
+            [
+                ("Roll", r_color, PidAxis::Roll, term),
+                ("Pitch", p_color, PidAxis::Pitch, term),
+                ("Yaw", y_color, PidAxis::Yaw, term),
+            ]
+        } else {
+            let axis = state.selected_pid_axis;
+            ui.label(format!("{} PID Values (P, I, D)", axis.name()));
+            [
+                ("P", RED, axis, PidTerm::P),
+                ("I", GREEN, axis, PidTerm::I),
+                ("D", BLUE, axis, PidTerm::D),
+            ]
         };
 
-        ui.label(format!("{axis_name} PID Values (P, I, D)"));
+        let view = PlotView::of(ui, state);
+        let cursor_x = state.plot_cursor_x;
+        let buffer = state.data_buffer.lock().unwrap();
+        let events = buffer.event_markers(view.x_mode);
+        if !has_plottable_range(&buffer, view) {
+            ui.label("Waiting for telemetry…");
+            return;
+        }
+        let plot_height = (ui.ctx().screen_rect().height() * 0.20).min(200.0);
+        let plot_width = ui.available_width();
+
+        let lines: Vec<SeriesPoints> = signals
+            .iter()
+            .map(|&(_, _, axis, term)| {
+                let id = format!("pid_{}_{:?}", axis.name(), term);
+                series(&buffer, view, &id, |d| d.pid_term(axis, term) as f64)
+            })
+            .collect();
 
+        let response = Plot::new("pid_plot")
+            .legend(Legend::default())
+            .label_formatter(|_, point| {
+                nearest_sample_label(&buffer, view, point.x, |d| {
+                    signals
+                        .iter()
+                        .map(|&(name, _, axis, term)| format!("{}: {:.3}", name, d.pid_term(axis, term)))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+            })
+            .height(plot_height)
+            .width(plot_width)
+            .show(ui, |plot_ui| {
+                draw_cursor(plot_ui, cursor_x);
+                draw_event_markers(plot_ui, &events);
+                for ((name, color, _, _), data) in signals.iter().zip(&lines) {
+                    plot_ui.line(
+                        plot_line(data, stepped)
+                            .name(*name)
+                            .color(*color)
+                            .width(persistent_settings.plot_style.line_width),
+                    );
+                }
+                for ((_, color, _, _), data) in signals.iter().zip(&lines) {
+                    plot_peaks(plot_ui, data, *color, 0.05);
+                }
+            });
+        let [(n0, c0, a0, t0), (n1, c1, a1, t1), (n2, c2, a2, t2)] = signals;
+        render_stats_row(
+            ui,
+            &buffer,
+            view.window,
+            &[
+                (n0, c0, &|d| d.pid_term(a0, t0) as f64),
+                (n1, c1, &|d| d.pid_term(a1, t1) as f64),
+                (n2, c2, &|d| d.pid_term(a2, t2) as f64),
+            ],
+        );
+        track_hover(ui, &mut state.plot_hover_x, &response, &events);
+    });
+    offer_capture_rect(ui, state, "pid_plot", group.response.rect);
+}
+
+/// Colors cycled through for user-selected fields in the custom plot
+const CUSTOM_PLOT_COLORS: [Color32; 6] = [
+    Color32::from_rgb(255, 140, 0),
+    Color32::from_rgb(0, 160, 210),
+    Color32::from_rgb(255, 0, 200),
+    Color32::from_rgb(110, 170, 0),
+    Color32::from_rgb(190, 140, 40),
+    Color32::from_rgb(110, 110, 230),
+];
+
+/// Renders a plot of any telemetry fields picked by name
+pub fn render_custom_plot(ui: &mut egui::Ui, state: &mut AppState) {
+    let max_width = ui.ctx().screen_rect().width() - 32.0;
+    ui.set_max_width(max_width);
+    ui.group(|ui| {
+        ui.set_max_width(max_width - 16.0);
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Custom Plot:");
+            let mut remove = None;
+            for (i, name) in state.custom_plot_fields.iter().enumerate() {
+                if ui.small_button(format!("{name} ✖")).on_hover_text("Remove").clicked() {
+                    remove = Some(i);
+                }
+            }
+            if let Some(i) = remove {
+                state.custom_plot_fields.remove(i);
+            }
+            egui::ComboBox::from_id_salt("custom_plot_add")
+                .selected_text("Add field…")
+                .show_ui(ui, |ui| {
+                    for name in TelemetryData::FIELD_NAMES {
+                        let selected = state.custom_plot_fields.iter().any(|f| f == name);
+                        if ui.selectable_label(selected, name).clicked() && !selected {
+                            state.custom_plot_fields.push(name.to_string());
+                        }
+                    }
+                });
+            step_toggle(ui, state, "custom_plot");
+        });
+        let stepped = state.stepped_plots.contains("custom_plot");
+
+        if state.custom_plot_fields.is_empty() {
+            return;
+        }
+
+        let view = PlotView::of(ui, state);
+        let cursor_x = state.plot_cursor_x;
         let buffer = state.data_buffer.lock().unwrap();
-        if !has_plottable_range(&buffer.data) {
+        let events = buffer.event_markers(view.x_mode);
+        if !has_plottable_range(&buffer, view) {
             ui.label("Waiting for telemetry…");
             return;
         }
         let plot_height = (ui.ctx().screen_rect().height() * 0.20).min(200.0);
         let plot_width = ui.available_width();
 
-        let p_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| {
-            let val = match selected_axis { PidAxis::Roll => d.roll_p, PidAxis::Pitch => d.pitch_p, PidAxis::Yaw => d.yaw_p };
-            [d.timestamp as f64 / 1000.0, val as f64]
-        }).collect();
-        let i_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| {
-            let val = match selected_axis { PidAxis::Roll => d.roll_i, PidAxis::Pitch => d.pitch_i, PidAxis::Yaw => d.yaw_i };
-            [d.timestamp as f64 / 1000.0, val as f64]
-        }).collect();
-        let d_data: Vec<[f64; 2]> = buffer.data.iter().map(|d| {
-            let val = match selected_axis { PidAxis::Roll => d.roll_d, PidAxis::Pitch => d.pitch_d, PidAxis::Yaw => d.yaw_d };
-            [d.timestamp as f64 / 1000.0, val as f64]
-        }).collect();
-
-        Plot::new("pid_plot")
+        let lines: Vec<(&str, SeriesPoints)> = state
+            .custom_plot_fields
+            .iter()
+            .map(|name| {
+                let id = format!("field_{}", name);
+                (name.as_str(), series(&buffer, view, &id, |d| d.field(name).unwrap_or_default()))
+            })
+            .collect();
+
+        let response = Plot::new("custom_plot")
             .legend(Legend::default())
             .height(plot_height)
             .width(plot_width)
             .show(ui, |plot_ui| {
-                let p_color = Color32::from_rgb(255, 100, 100);
-                let i_color = Color32::from_rgb(100, 255, 100);
-                let d_color = Color32::from_rgb(100, 100, 255);
-                plot_ui.line(Line::new(p_data.clone()).name("P").color(p_color));
-                plot_ui.line(Line::new(i_data.clone()).name("I").color(i_color));
-                plot_ui.line(Line::new(d_data.clone()).name("D").color(d_color));
-                plot_peaks(plot_ui, &p_data, p_color, 0.05);
-                plot_peaks(plot_ui, &i_data, i_color, 0.05);
-                plot_peaks(plot_ui, &d_data, d_color, 0.05);
+                draw_cursor(plot_ui, cursor_x);
+                draw_event_markers(plot_ui, &events);
+                for (i, (name, data)) in lines.iter().enumerate() {
+                    let color = CUSTOM_PLOT_COLORS[i % CUSTOM_PLOT_COLORS.len()];
+                    plot_ui.line(plot_line(data, stepped).name(*name).color(color));
+                }
             });
+        track_hover(ui, &mut state.plot_hover_x, &response, &events);
     });
 }
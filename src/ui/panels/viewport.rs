@@ -1,11 +1,24 @@
 use crate::app::AppState;
+use crate::persistence::PersistentSettings;
+use crate::telemetry::AngleUnit;
 use bevy_egui::egui;
 use egui::Color32;
 
 /// Renders the 3D viewport section with orientation display
-pub fn render_viewport_section(ui: &mut egui::Ui, state: &AppState, width: f32) {
+pub fn render_viewport_section(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    persistent_settings: &mut PersistentSettings,
+    width: f32,
+) {
     ui.vertical(|ui| {
-        ui.label("3D Drone View");
+        ui.horizontal(|ui| {
+            ui.label("3D Drone View");
+            ui.checkbox(&mut state.show_artificial_horizon, "Horizon");
+            ui.checkbox(&mut persistent_settings.show_grid, "Grid");
+            ui.checkbox(&mut persistent_settings.show_axes, "Axes")
+                .on_hover_text("World axes at the origin: X red, Y green (up), Z blue");
+        });
         ui.set_width(width);
         let viewport_height = width * 0.75; // Match render target aspect
 
@@ -19,74 +32,51 @@ pub fn render_viewport_section(ui: &mut egui::Ui, state: &AppState, width: f32)
             ui.label("Loading 3D view...");
         }
 
+        if state.show_artificial_horizon {
+            let attitude = state
+                .data_buffer
+                .lock()
+                .ok()
+                .and_then(|b| b.data.back().map(|d| (d.roll, d.pitch)));
+            let (roll, pitch) = attitude.unwrap_or_default();
+            render_artificial_horizon(ui, roll, pitch, width, state.angle_unit);
+        }
+
         // Current values in a styled box
         egui::Frame::group(ui.style())
             .inner_margin(egui::Margin::same(8.0))
             .show(ui, |ui| {
+                let unit = state.angle_unit;
                 let buffer = state.data_buffer.lock().unwrap();
                 if let Some(latest) = buffer.data.back() {
                     ui.vertical(|ui| {
-                        // Roll with red background
-                        ui.scope(|ui| {
-                            egui::Frame::none()
-                                .inner_margin(egui::Margin::symmetric(6.0, 4.0))
-                                .fill(Color32::from_rgb(80, 20, 20))
-                                .rounding(egui::Rounding::same(4.0))
-                                .show(ui, |ui| {
-                                    ui.label(
-                                        egui::RichText::new(format!(
-                                            "Roll: {:.2}°",
-                                            latest.roll.to_degrees()
-                                        ))
-                                        .color(Color32::from_rgb(255, 100, 100))
-                                        .monospace(),
-                                    );
-                                });
-                        });
-
-                        // Pitch with green background
-                        ui.scope(|ui| {
-                            egui::Frame::none()
-                                .inner_margin(egui::Margin::symmetric(6.0, 4.0))
-                                .fill(Color32::from_rgb(20, 80, 20))
-                                .rounding(egui::Rounding::same(4.0))
-                                .show(ui, |ui| {
-                                    ui.label(
-                                        egui::RichText::new(format!(
-                                            "Pitch: {:.2}°",
-                                            latest.pitch.to_degrees()
-                                        ))
-                                        .color(Color32::from_rgb(100, 255, 100))
-                                        .monospace(),
-                                    );
-                                });
-                        });
-
-                        // Yaw with blue background
-                        ui.scope(|ui| {
+                        // Attitude readouts tinted with the plot colors
+                        let colors = persistent_settings.plot_style.axis_colors();
+                        let values = [("Roll", latest.roll), ("Pitch", latest.pitch), ("Yaw", latest.yaw)];
+                        for ((name, value), color) in values.into_iter().zip(colors) {
                             egui::Frame::none()
                                 .inner_margin(egui::Margin::symmetric(6.0, 4.0))
-                                .fill(Color32::from_rgb(20, 20, 80))
+                                .fill(color.linear_multiply(0.25))
                                 .rounding(egui::Rounding::same(4.0))
                                 .show(ui, |ui| {
                                     ui.label(
-                                        egui::RichText::new(format!(
-                                            "Yaw: {:.2}°",
-                                            latest.yaw.to_degrees()
-                                        ))
-                                        .color(Color32::from_rgb(100, 100, 255))
-                                        .monospace(),
+                                        egui::RichText::new(format!("{}: {}", name, unit.format(value)))
+                                            .color(color)
+                                            .monospace(),
                                     );
                                 });
-                        });
+                        }
 
                         ui.add_space(4.0);
 
                         // Gyro rates
                         ui.label(
                             egui::RichText::new(format!(
-                                "Gyro: {:.1} / {:.1} / {:.1} rad/s",
-                                latest.gyro_x, latest.gyro_y, latest.gyro_z
+                                "Gyro: {:.1} / {:.1} / {:.1} {}/s",
+                                unit.convert(latest.gyro_x as f64),
+                                unit.convert(latest.gyro_y as f64),
+                                unit.convert(latest.gyro_z as f64),
+                                unit.name()
                             ))
                             .monospace()
                             .color(Color32::GRAY),
@@ -109,8 +99,24 @@ pub fn render_viewport_section(ui: &mut egui::Ui, state: &AppState, width: f32)
                                 latest.height
                             ))
                             .monospace()
-                            .color(Color32::from_rgb(255, 255, 100)),
+                            .color(if ui.visuals().dark_mode {
+                                Color32::from_rgb(255, 255, 100)
+                            } else {
+                                Color32::from_rgb(150, 110, 0)
+                            }),
                         );
+
+                        // GPS, only on firmware that sends it
+                        if let (Some(lat), Some(lon)) = (latest.lat, latest.lon) {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "GPS: {:.6}, {:.6} ({} sats)",
+                                    lat, lon, latest.sats
+                                ))
+                                .monospace()
+                                .color(Color32::GRAY),
+                            );
+                        }
                     });
                 } else {
                     ui.label("No data received yet");
@@ -118,3 +124,88 @@ pub fn render_viewport_section(ui: &mut egui::Ui, state: &AppState, width: f32)
             });
     });
 }
+
+/// Degrees of pitch visible between the center and the top/bottom edge
+const HORIZON_HALF_RANGE_DEG: f32 = 30.0;
+
+/// Classic attitude indicator: sky/ground split rotated by roll and shifted by
+/// pitch, a pitch ladder every 10°, and a fixed aircraft symbol. Angles in radians.
+fn render_artificial_horizon(
+    ui: &mut egui::Ui,
+    roll: f32,
+    pitch: f32,
+    width: f32,
+    unit: AngleUnit,
+) {
+    let size = egui::vec2(width, width * 0.6);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let center = rect.center();
+    let px_per_deg = rect.height() / (2.0 * HORIZON_HALF_RANGE_DEG);
+
+    // Horizon frame: x along the horizon, y up in degrees above the horizon.
+    // The horizon turns opposite to the aircraft's roll; sin/cos make any roll wrap harmless.
+    let (sin, cos) = (-roll).sin_cos();
+    let pitch_deg = pitch.to_degrees();
+    let to_screen = |x: f32, y_deg: f32| {
+        let y = -(y_deg - pitch_deg) * px_per_deg;
+        center + egui::vec2(x * cos - y * sin, x * sin + y * cos)
+    };
+
+    // Sky background, then the ground as a large polygon below the horizon
+    painter.rect_filled(rect, 4.0, Color32::from_rgb(40, 110, 190));
+    let far = rect.width() + rect.height();
+    let far_deg = far / px_per_deg;
+    painter.add(egui::Shape::convex_polygon(
+        vec![
+            to_screen(-far, 0.0),
+            to_screen(far, 0.0),
+            to_screen(far, -far_deg),
+            to_screen(-far, -far_deg),
+        ],
+        Color32::from_rgb(130, 85, 40),
+        egui::Stroke::NONE,
+    ));
+    painter.line_segment([to_screen(-far, 0.0), to_screen(far, 0.0)], egui::Stroke::new(2.0, Color32::WHITE));
+
+    // Pitch ladder
+    let ladder_stroke = egui::Stroke::new(1.0, Color32::WHITE);
+    for step in [-30, -20, -10, 10, 20, 30] {
+        let deg = step as f32;
+        let half = if step % 20 == 0 { rect.width() * 0.15 } else { rect.width() * 0.1 };
+        painter.line_segment([to_screen(-half, deg), to_screen(half, deg)], ladder_stroke);
+        painter.text(
+            to_screen(half + 4.0, deg),
+            egui::Align2::LEFT_CENTER,
+            format!("{step}"),
+            egui::FontId::monospace(9.0),
+            Color32::WHITE,
+        );
+    }
+
+    // Roll pointer at the top of the dial, rotating with the horizon
+    let pointer_radius = rect.height() * 0.45;
+    let up = egui::vec2(sin, -cos);
+    let tip = center + up * pointer_radius;
+    let side = egui::vec2(cos, sin) * 5.0;
+    painter.add(egui::Shape::convex_polygon(
+        vec![tip, tip - up * 8.0 + side, tip - up * 8.0 - side],
+        Color32::WHITE,
+        egui::Stroke::NONE,
+    ));
+
+    // Fixed aircraft symbol
+    let wing = rect.width() * 0.12;
+    let symbol = egui::Stroke::new(3.0, Color32::YELLOW);
+    painter.line_segment([center - egui::vec2(wing * 2.0, 0.0), center - egui::vec2(wing, 0.0)], symbol);
+    painter.line_segment([center + egui::vec2(wing, 0.0), center + egui::vec2(wing * 2.0, 0.0)], symbol);
+    painter.circle_filled(center, 3.0, Color32::YELLOW);
+
+    painter.text(
+        rect.left_bottom() + egui::vec2(4.0, -4.0),
+        egui::Align2::LEFT_BOTTOM,
+        format!("R {}  P {}", unit.format(roll), unit.format(pitch)),
+        egui::FontId::monospace(10.0),
+        Color32::WHITE,
+    );
+}
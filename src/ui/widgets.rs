@@ -0,0 +1,125 @@
+// Input widgets shared by the command and PID panels that adapt to touch mode
+
+use bevy_egui::egui;
+use std::ops::RangeInclusive;
+
+/// Side of the -/+ stepper buttons in touch mode, roughly a fingertip
+const TOUCH_TARGET: f32 = 44.0;
+
+/// Numeric field: a DragValue normally, or in touch mode a slider between large
+/// -/+ steppers. The response reports `changed()` for either.
+pub struct NumberInput<'a> {
+    value: &'a mut f32,
+    range: RangeInclusive<f32>,
+    speed: f64,
+    step: f32,
+    prefix: &'static str,
+    suffix: &'static str,
+    touch: bool,
+}
+
+impl<'a> NumberInput<'a> {
+    pub fn new(value: &'a mut f32, range: RangeInclusive<f32>) -> Self {
+        Self {
+            value,
+            range,
+            speed: 0.01,
+            step: 0.01,
+            prefix: "",
+            suffix: "",
+            touch: false,
+        }
+    }
+
+    /// Drag speed of the desktop DragValue
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Change per press of a touch-mode stepper
+    pub fn step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    pub fn prefix(mut self, prefix: &'static str) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    pub fn suffix(mut self, suffix: &'static str) -> Self {
+        self.suffix = suffix;
+        self
+    }
+
+    pub fn touch(mut self, touch: bool) -> Self {
+        self.touch = touch;
+        self
+    }
+}
+
+impl egui::Widget for NumberInput<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let Self { value, range, speed, step, prefix, suffix, touch } = self;
+        if !touch {
+            return ui.add(
+                egui::DragValue::new(value)
+                    .range(range)
+                    .speed(speed)
+                    .prefix(prefix)
+                    .suffix(suffix),
+            );
+        }
+
+        ui.horizontal(|ui| {
+            let stepper = |ui: &mut egui::Ui, text: &str| {
+                let button = egui::Button::new(egui::RichText::new(text).heading())
+                    .min_size(egui::vec2(TOUCH_TARGET, TOUCH_TARGET));
+                ui.add(button).clicked()
+            };
+            let down = stepper(ui, "−");
+            let mut response = ui.add(
+                egui::Slider::new(value, range.clone())
+                    .prefix(prefix)
+                    .suffix(suffix),
+            );
+            let up = stepper(ui, "+");
+            let steps = up as i32 - down as i32;
+            if steps != 0 {
+                *value = (*value + steps as f32 * step).clamp(*range.start(), *range.end());
+                response.mark_changed();
+            }
+            response
+        })
+        .inner
+    }
+}
+
+/// Scales up spacing and text for a touchscreen, or restores egui's defaults.
+/// Only touches the style when the mode changes.
+pub fn apply_touch_style(ctx: &egui::Context, touch: bool) {
+    let id = egui::Id::new("touch_style_applied");
+    if ctx.data(|data| data.get_temp::<bool>(id)).unwrap_or(false) == touch {
+        return;
+    }
+    ctx.data_mut(|data| data.insert_temp(id, touch));
+
+    let defaults = egui::Style::default();
+    ctx.all_styles_mut(|style| {
+        style.spacing = defaults.spacing.clone();
+        style.text_styles = defaults.text_styles.clone();
+        if touch {
+            let spacing = &mut style.spacing;
+            spacing.item_spacing = egui::vec2(12.0, 10.0);
+            spacing.button_padding = egui::vec2(12.0, 8.0);
+            spacing.interact_size = egui::vec2(48.0, 36.0);
+            spacing.slider_width = 180.0;
+            spacing.icon_width = 24.0;
+            spacing.icon_spacing = 8.0;
+            for font in style.text_styles.values_mut() {
+                font.size *= 1.25;
+            }
+        }
+    });
+}
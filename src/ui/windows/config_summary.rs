@@ -0,0 +1,77 @@
+use bevy::log::error;
+use crate::app::{AppState, CommandQueue};
+use crate::persistence::PersistentSettings;
+use crate::protocol;
+use bevy_egui::egui;
+
+/// Renders the read-only view of the ConfigPacket the UI would send, next to the last one sent
+pub fn render_config_summary_window(
+    ctx: &egui::Context,
+    state: &mut AppState,
+    command_queue: &CommandQueue,
+    persistent_settings: &PersistentSettings,
+) {
+    let mut show_config_summary = state.show_config_summary;
+
+    if show_config_summary {
+        egui::Window::new("Config")
+            .open(&mut show_config_summary)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let config = persistent_settings.to_config_packet();
+                let (last_sent, calibrating) = state
+                    .data_buffer
+                    .lock()
+                    .map(|buffer| (buffer.commands.last_config, buffer.commands.is_calibrating()))
+                    .unwrap_or_default();
+
+                ui.label("Exact ConfigPacket that would be sent; changed fields are highlighted");
+                ui.separator();
+                render_config_fields(ui, &config, last_sent.as_ref());
+                ui.separator();
+
+                ui.add_enabled_ui(state.uart_sender.is_some() && !calibrating, |ui| {
+                    if ui.button("Send This Config").clicked() {
+                        if let Err(e) = protocol::send_command_config(command_queue, config) {
+                            error!("Failed to send config: {}", e);
+                        } else if let Ok(mut buffer) = state.data_buffer.lock() {
+                            buffer.push_log(format!("Flight config sent: {}", config.summary()));
+                        }
+                    }
+                });
+            });
+
+        state.show_config_summary = show_config_summary;
+    }
+}
+
+fn render_config_fields(
+    ui: &mut egui::Ui,
+    config: &protocol::ConfigPacket,
+    last_sent: Option<&protocol::ConfigPacket>,
+) {
+    let changed_color = ui.visuals().warn_fg_color;
+    egui::Grid::new("config_summary_grid")
+        .striped(true)
+        .num_columns(3)
+        .show(ui, |ui| {
+            ui.strong("Field");
+            ui.strong("Current");
+            ui.strong("Last sent");
+            ui.end_row();
+
+            let sent_fields = last_sent.map(|c| c.fields());
+            for (i, (name, value)) in config.fields().into_iter().enumerate() {
+                let sent = sent_fields.map(|fields| fields[i].1);
+                let changed = sent.is_some_and(|sent| sent != value);
+                let text = egui::RichText::new(format!("{:.4}", value)).monospace();
+                ui.label(name);
+                ui.label(if changed { text.color(changed_color) } else { text });
+                ui.label(match sent {
+                    Some(sent) => egui::RichText::new(format!("{:.4}", sent)).monospace(),
+                    None => egui::RichText::new("—").weak(),
+                });
+                ui.end_row();
+            }
+        });
+}
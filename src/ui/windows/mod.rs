@@ -1,3 +1,7 @@
+pub mod config_summary;
 pub mod pid_tuning;
+pub mod reset_settings;
 
+pub use config_summary::render_config_summary_window;
 pub use pid_tuning::render_pid_tuning_window;
+pub use reset_settings::render_reset_confirmation_window;
@@ -1,6 +1,8 @@
+use bevy::log::error;
 use crate::app::{AppState, CommandQueue};
-use crate::persistence::PersistentSettings;
+use crate::persistence::{PersistentSettings, PidCheck, PidWarningThresholds};
 use crate::protocol;
+use crate::ui::widgets::NumberInput;
 use bevy_egui::egui;
 
 /// Renders the PID tuning window
@@ -32,7 +34,17 @@ pub fn render_pid_tuning_window(
                 ui.add_space(10.0);
                 ui.separator();
 
-                render_send_controls(ui, state, command_queue, persistent_settings);
+                let check = persistent_settings
+                    .get_pid(persistent_settings.selected_tune_axis)
+                    .check(&persistent_settings.pid_warning_thresholds);
+                let touch = persistent_settings.touch_mode;
+                render_pid_check(ui, &check, &mut persistent_settings.pid_warning_thresholds, touch);
+                ui.separator();
+
+                render_send_selected(ui, state, command_queue, persistent_settings);
+                ui.separator();
+
+                render_send_controls(ui, state, command_queue, persistent_settings, check.errors.is_empty());
             });
 
         state.show_pid_tuning = show_pid_tuning;
@@ -77,81 +89,200 @@ fn render_axis_selection(ui: &mut egui::Ui, persistent_settings: &mut Persistent
 
 fn render_pid_parameters(ui: &mut egui::Ui, persistent_settings: &mut PersistentSettings) {
     let selected_axis = persistent_settings.selected_tune_axis;
+    let step = persistent_settings.pid_nudge_step;
+    let touch = persistent_settings.touch_mode;
     let pid_params = persistent_settings.get_pid_mut(selected_axis);
 
     ui.add_space(10.0);
     ui.horizontal(|ui| {
         ui.label("P (Proportional):");
-        ui.add(
-            egui::DragValue::new(&mut pid_params.p)
-                .speed(0.01)
-                .range(-20.0..=20.0),
-        );
+        gain_drag_value(ui, &mut pid_params.p, 0.01, -20.0..=20.0, step, touch);
     });
 
     ui.horizontal(|ui| {
         ui.label("I (Integral):");
-        ui.add(
-            egui::DragValue::new(&mut pid_params.i)
-                .speed(0.001)
-                .range(-20.0..=20.0),
-        );
+        gain_drag_value(ui, &mut pid_params.i, 0.001, -20.0..=20.0, step, touch);
     });
 
     ui.horizontal(|ui| {
         ui.label("D (Derivative):");
+        gain_drag_value(ui, &mut pid_params.d, 0.001, -2.0..=2.0, step, touch);
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Nudge step:");
         ui.add(
-            egui::DragValue::new(&mut pid_params.d)
-                .speed(0.001)
-                .range(-2.0..=2.0),
+            NumberInput::new(&mut persistent_settings.pid_nudge_step, 0.0001..=1.0)
+                .speed(0.0001)
+                .step(0.001)
+                .touch(touch),
         );
+        if touch {
+            ui.weak("step of the -/+ buttons");
+        } else {
+            ui.weak("↑/↓ in a focused field, PgUp/PgDn ×10");
+        }
     });
 }
 
 fn render_pid_limits(ui: &mut egui::Ui, persistent_settings: &mut PersistentSettings) {
     let selected_axis = persistent_settings.selected_tune_axis;
+    let step = persistent_settings.pid_nudge_step;
+    let touch = persistent_settings.touch_mode;
     let pid_params = persistent_settings.get_pid_mut(selected_axis);
 
     ui.horizontal(|ui| {
         ui.label("I Limit:");
-        ui.add(
-            egui::DragValue::new(&mut pid_params.i_limit)
-                .speed(0.1)
-                .range(0.0..=50.0),
-        );
+        gain_drag_value(ui, &mut pid_params.i_limit, 0.1, 0.0..=50.0, step, touch);
     });
 
     ui.horizontal(|ui| {
         ui.label("PID Limit:");
-        ui.add(
-            egui::DragValue::new(&mut pid_params.pid_limit)
-                .speed(0.1)
-                .range(0.0..=100.0),
-        );
+        gain_drag_value(ui, &mut pid_params.pid_limit, 0.1, 0.0..=100.0, step, touch);
     });
 }
 
+/// DragValue that, while focused, steps by `step` on ArrowUp/ArrowDown and by
+/// ten steps on PageUp/PageDown, overriding the drag speed for arrow keys.
+/// In touch mode it is a slider with -/+ buttons stepping by `step` instead.
+fn gain_drag_value(
+    ui: &mut egui::Ui,
+    value: &mut f32,
+    speed: f64,
+    range: std::ops::RangeInclusive<f32>,
+    step: f32,
+    touch: bool,
+) -> egui::Response {
+    if touch {
+        return ui.add(NumberInput::new(value, range).step(step).touch(true));
+    }
+    let id = ui.next_auto_id();
+    if ui.memory(|mem| mem.has_focus(id)) {
+        let steps = ui.input_mut(|input| {
+            let count = |input: &mut egui::InputState, key| {
+                input.count_and_consume_key(egui::Modifiers::NONE, key) as f32
+            };
+            (count(input, egui::Key::ArrowUp) - count(input, egui::Key::ArrowDown))
+                + 10.0 * (count(input, egui::Key::PageUp) - count(input, egui::Key::PageDown))
+        });
+        if steps != 0.0 {
+            *value = (*value + steps * step).clamp(*range.start(), *range.end());
+            // Drop the in-progress text so the field shows the nudged value
+            ui.data_mut(|data| data.remove::<String>(id));
+        }
+    }
+    ui.add(egui::DragValue::new(value).speed(speed).range(range))
+}
+
+/// Blocking errors in red and rule-of-thumb warnings in yellow, plus the warning thresholds
+fn render_pid_check(ui: &mut egui::Ui, check: &PidCheck, thresholds: &mut PidWarningThresholds, touch: bool) {
+    for error in &check.errors {
+        ui.colored_label(ui.visuals().error_fg_color, format!("⛔ {}", error));
+    }
+    for warning in &check.warnings {
+        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", warning));
+    }
+    egui::CollapsingHeader::new("Warning thresholds")
+        .id_salt("pid_warning_thresholds")
+        .show(ui, |ui| {
+            egui::Grid::new("pid_warning_thresholds_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Max P without D");
+                ui.add(NumberInput::new(&mut thresholds.max_p_without_d, 0.0..=20.0).speed(0.05).step(0.1).touch(touch));
+                ui.end_row();
+                ui.label("Max D / P");
+                ui.add(NumberInput::new(&mut thresholds.max_d_to_p, 0.0..=10.0).speed(0.05).step(0.1).touch(touch));
+                ui.end_row();
+                ui.label("Max I / P");
+                ui.add(NumberInput::new(&mut thresholds.max_i_to_p, 0.0..=10.0).speed(0.05).step(0.1).touch(touch));
+                ui.end_row();
+            });
+            if ui.button("Reset thresholds").clicked() {
+                *thresholds = PidWarningThresholds::default();
+            }
+        });
+}
+
+/// Axis checkboxes and a button sending the gains of every checked axis at once
+fn render_send_selected(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    command_queue: &CommandQueue,
+    persistent_settings: &PersistentSettings,
+) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Send axes:");
+        for axis in protocol::SelectPID::ALL {
+            let mut checked = state.pid_send_axes.contains(&axis);
+            if ui.checkbox(&mut checked, axis.label()).changed() {
+                state.pid_send_axes.retain(|&a| a != axis);
+                if checked {
+                    state.pid_send_axes.push(axis);
+                }
+            }
+        }
+    });
+
+    // Send in the usual axis order, whatever order they were checked in
+    let axes: Vec<protocol::SelectPID> = protocol::SelectPID::ALL
+        .into_iter()
+        .filter(|axis| state.pid_send_axes.contains(axis))
+        .collect();
+    let invalid_axis = axes.iter().copied().find(|&axis| {
+        !persistent_settings
+            .get_pid(axis)
+            .check(&persistent_settings.pid_warning_thresholds)
+            .errors
+            .is_empty()
+    });
+    let connected = state.uart_sender.is_some();
+    let calibrating = state
+        .data_buffer
+        .lock()
+        .is_ok_and(|buffer| buffer.commands.is_calibrating());
+    let send = ui
+        .add_enabled(
+            connected && !calibrating && !axes.is_empty() && invalid_axis.is_none(),
+            egui::Button::new("Send Selected"),
+        )
+        .on_hover_text("One tune command per checked axis; unchecked axes stay as they are on the drone");
+    let send = match invalid_axis {
+        Some(axis) => send.on_disabled_hover_text(format!("{} PID gains have errors", axis.label())),
+        None => send,
+    };
+    if send.clicked() {
+        let pids: Vec<_> = axes
+            .iter()
+            .map(|&axis| (axis, persistent_settings.get_pid(axis).to_controller()))
+            .collect();
+        if let Err(e) = protocol::send_command_tune_pids(command_queue, &pids) {
+            error!("Failed to send tune PID: {}", e);
+        } else if let Ok(mut buffer) = state.data_buffer.lock() {
+            let names: Vec<&str> = axes.iter().map(|axis| axis.label()).collect();
+            buffer.push_log(format!("PID tune sent for {}", names.join(", ")));
+        }
+    }
+}
+
 fn render_send_controls(
     ui: &mut egui::Ui,
     state: &mut AppState,
     command_queue: &CommandQueue,
     persistent_settings: &PersistentSettings,
+    gains_valid: bool,
 ) {
     ui.horizontal(|ui| {
         let connected = state.uart_sender.is_some();
-        ui.add_enabled_ui(connected, |ui| {
-            if ui.button("Send Tune").clicked() {
+        let calibrating = state
+            .data_buffer
+            .lock()
+            .is_ok_and(|buffer| buffer.commands.is_calibrating());
+        ui.add_enabled_ui(connected && !calibrating, |ui| {
+            let send = ui.add_enabled(gains_valid, egui::Button::new("Send Tune"));
+            if send.on_disabled_hover_text("Fix the errors above first").clicked() {
                 let axis = persistent_settings.selected_tune_axis;
-                let params = persistent_settings.get_pid(axis);
-                let pid = protocol::PIDController {
-                    p: params.p,
-                    i: params.i,
-                    d: params.d,
-                    i_limit: params.i_limit,
-                    pid_limit: params.pid_limit,
-                };
+                let pid = persistent_settings.get_pid(axis).to_controller();
                 if let Err(e) = protocol::send_command_tune_pid(command_queue, axis, pid) {
-                    eprintln!("Failed to send tune PID: {}", e);
+                    error!("Failed to send tune PID: {}", e);
                 } else if let Ok(mut buffer) = state.data_buffer.lock() {
                     buffer.push_log(format!("PID tune sent for {:?}", axis));
                 }
@@ -159,7 +290,7 @@ fn render_send_controls(
 
             if ui.button("Save").clicked() {
                 if let Err(e) = protocol::send_command_save(command_queue) {
-                    eprintln!("Failed to send save command: {}", e);
+                    error!("Failed to send save command: {}", e);
                 } else if let Ok(mut buffer) = state.data_buffer.lock() {
                     buffer.push_log("Save to flash queued".to_string());
                 }
@@ -0,0 +1,44 @@
+use crate::app::AppState;
+use crate::persistence::{PersistentSettings, SettingsReset};
+use bevy_egui::egui;
+
+/// Asks before a reset to defaults chosen from the Settings menu, keeping the
+/// current settings for undo when confirmed
+pub fn render_reset_confirmation_window(
+    ctx: &egui::Context,
+    state: &mut AppState,
+    persistent_settings: &mut PersistentSettings,
+) {
+    let Some(scope) = state.pending_settings_reset else {
+        return;
+    };
+
+    let mut confirmed = false;
+    let mut cancelled = false;
+    egui::Window::new("Reset to Defaults")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(format!("Reset {} to defaults?", scope.label().to_lowercase()));
+            if scope == SettingsReset::Everything {
+                ui.label("This also forgets connection profiles, layout and log settings.");
+            }
+            ui.weak("The settings file is overwritten; Settings > Undo reset restores it.");
+            ui.horizontal(|ui| {
+                confirmed = ui.button("Reset").clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+
+    if confirmed {
+        state.settings_undo = Some((scope, persistent_settings.clone()));
+        persistent_settings.reset(scope);
+        if let Ok(mut log) = state.log_buffer.lock() {
+            log.push_log(format!("Reset {} to defaults", scope.label().to_lowercase()));
+        }
+    }
+    if confirmed || cancelled {
+        state.pending_settings_reset = None;
+    }
+}